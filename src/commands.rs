@@ -12,34 +12,17 @@ mod build;
 mod clean;
 mod init;
 mod serve;
+mod test;
+mod watch;
 
 #[derive(Debug, Subcommand)]
 pub enum Commands {
     /// Creates the boilerplate structure and files for a new book
-    Init {
-        /// Copies the default theme into your source folder
-        #[clap(long, short)]
-        theme: Option<String>,
-        /// Sets the book title
-        #[clap(long)]
-        title: Option<String>,
-        /// Directory to create the book in
-        #[clap(value_hint = ValueHint::AnyPath)]
-        dir: PathBuf,
-    },
+    Init(init::CommandInit),
     /// Builds a book from its markdown files
     Build(build::CommandBuild),
     /// Deletes a built book
-    Clean {
-        /// Root directory for the book
-        #[clap(value_hint = ValueHint::DirPath)]
-        dir: PathBuf,
-        /// Relative paths are interpreted relative to the book's root directory.\n\
-        /// If omitted, mdBook uses build.build-dir from book.toml \
-        /// or defaults to `./book`.
-        #[clap(long, short, value_hint = ValueHint::DirPath)]
-        dest_dir: Option<PathBuf>,
-    },
+    Clean(clean::CommandClean),
     /// The completions command is used to generate auto-completions for some common shells
     Completions {
         #[clap(value_enum)]
@@ -49,20 +32,7 @@ pub enum Commands {
         out_dir: PathBuf,
     },
     /// Watches a book's files and rebuilds it on changes
-    Watch {
-        /// Opens the compiled book in a web browser
-        #[clap(long, short)]
-        open: bool,
-        /// Output directory for the book\n\
-        /// Relative paths are interpreted relative to the book's root directory.\n\
-        /// If omitted, mdBook uses build.build-dir from book.toml \
-        /// or defaults to `./book`.
-        #[clap(long, short, value_hint = ValueHint::DirPath)]
-        dest_dir: Option<PathBuf>,
-        /// Root directory for the book
-        #[clap(value_hint = ValueHint::AnyPath)]
-        dir: PathBuf,
-    },
+    Watch(watch::CommandWatch),
     /// Serves a book at http://localhost:3000, and rebuilds it on changes
     Serve {
         #[clap(long, short)]
@@ -79,23 +49,15 @@ pub enum Commands {
         /// Hostname to listen on for HTTP connections
         #[clap(long, short = 'n', default_value = "0.0.0.0", value_hint = ValueHint::Hostname)]
         hostname: Option<String>,
+        /// Serves symlinks of the output directory that point outside of it
+        #[clap(long)]
+        allow_symlinks: bool,
         /// Root directory for the book
         #[clap(value_hint = ValueHint::DirPath)]
-        dir: PathBuf,
+        dir: Option<PathBuf>,
     },
     /// Tests that a book's Rust code samples compile
-    Test {
-        #[clap(long, short)]
-        open: bool,
-        #[clap(long, short)]
-        chapter: Option<String>,
-        /// A comma-separated list of directories to add to the crate search path when building tests
-        #[clap(long, short, value_hint = ValueHint::FilePath, value_delimiter = ',')]
-        library_path: Vec<PathBuf>,
-        /// Root directory for the book
-        #[clap(value_hint = ValueHint::DirPath)]
-        dir: PathBuf,
-    },
+    Test(test::CommandTest),
 }
 
 impl Commands {
@@ -106,25 +68,24 @@ impl Commands {
                 let name = cmd.get_name().to_string();
                 generate_to(*shell, &mut cmd, name, out_dir).unwrap();
             }
-            Commands::Clean { dir, dest_dir } => {
-                clean::execute(dir.clone(), dest_dir.clone())?;
+            Commands::Clean(cmd) => {
+                clean::execute(cmd)?;
             }
-            Commands::Init { theme, title, dir } => {
-                init::execute(theme.clone(), title.clone(), dir)?
+            Commands::Init(cmd) => {
+                init::execute(cmd)?;
             }
             Commands::Build(cmd) => {
                 build::execute(cmd).await?;
             }
-            Commands::Watch {
-                open,
-                dest_dir,
-                dir,
-            } => {}
+            Commands::Watch(cmd) => {
+                watch::execute(cmd).await?;
+            }
             Commands::Serve {
                 open,
                 port,
                 dest_dir,
                 hostname,
+                allow_symlinks,
                 dir,
             } => {
                 let config = ServeConfig {
@@ -132,18 +93,15 @@ impl Commands {
                     hostname: hostname.to_owned(),
                     open: open.to_owned(),
                     dest_dir: dest_dir.to_owned(),
+                    dir: dir.clone().unwrap_or_else(|| PathBuf::from(".")),
+                    allow_symlinks: *allow_symlinks,
                 };
 
-                serve::execute(config)
-                    .await
-                    .expect("ERROR STARTING THE SERVE");
+                serve::execute(config).await?;
+            }
+            Commands::Test(cmd) => {
+                test::execute(cmd).await?;
             }
-            Commands::Test {
-                open,
-                chapter,
-                library_path,
-                dir,
-            } => {}
         }
 
         Ok(())
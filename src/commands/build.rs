@@ -1,22 +1,30 @@
+mod book;
+pub(super) use book::{Book, Rebuild};
 mod collect_summaries;
 use collect_summaries::collect_summaries;
 mod summary;
-pub(super) use summary::{Summary, SummaryError, SummaryParser};
+pub(super) use summary::{
+    chapter_base, chapter_with_extension, resolve_link, same_chapter, Diagnostics, Summary,
+    SummaryError, SummaryParser, TreeNode, TreeNodeKind,
+};
+mod navigation;
+mod render;
+mod theme;
 mod to_html;
 use to_html::to_html;
+pub(super) use to_html::{split_hidden_lines, CodeInfo};
 
 use std::collections::HashMap;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use anyhow::{anyhow, Result};
 use clap::{Parser, ValueHint};
+use markdown::mdast as ast;
 use tokio::fs;
 use tokio::io::AsyncReadExt;
 
-use crate::models::build_config::BuildConfig;
-
 #[derive(Clone, Debug, Parser)]
 pub struct CommandBuild {
     /// Opens the compiled book in a web browser
@@ -33,125 +41,130 @@ pub struct CommandBuild {
     dir: Option<PathBuf>,
 }
 
-pub async fn execute(
-    CommandBuild {
-        out_dir,
-        dir,
-        open: _,
-    }: &CommandBuild,
-) -> Result<()> {
+pub async fn execute(CommandBuild { out_dir, dir, open }: &CommandBuild) -> Result<()> {
     let dir = dir
         .as_ref()
         .unwrap_or(&PathBuf::from_str(".").unwrap())
         .canonicalize()?;
 
-    assert!(std::env::set_current_dir(&dir).is_ok());
+    let book = Book::load(&dir, out_dir.as_deref()).await?;
+    book.build().await?;
+
+    println!("Book built at {}", book.build_dir.display());
 
-    let config = BuildConfig::from_path(dir.join("book.toml")).await?;
+    if *open {
+        open_book(&book.build_dir);
+    }
 
-    let build_dir = out_dir.as_ref().unwrap_or(&config.build_dir);
+    Ok(())
+}
 
-    match fs::create_dir(&build_dir).await {
-        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
-        err @ Ok(_) | err @ Err(_) => err?,
-    };
+/// Opens the index page of a built book in the web browser
+pub(super) fn open_book(build_dir: &Path) {
+    let index = build_dir.join("index.html");
 
-    let _build_dir = build_dir.canonicalize()?;
+    if let Err(err) = webbrowser::open(&index.to_string_lossy()) {
+        log::error!("Cannot open {index:?} in the web browser.\n  Cause: {err}");
+    }
+}
 
-    let src_dir = dir.join("src");
+pub(super) async fn read_chapter(file: &Path) -> Result<ast::Node> {
+    let file_base = chapter_base(file);
 
-    let collection = collect_summaries(&src_dir).await?;
+    let file_md = fs::File::open(chapter_with_extension(file, "md"));
+    let file_mdx = fs::File::open(chapter_with_extension(file, "mdx"));
 
-    if collection.summaries.is_empty() {
-        eprintln!("No \"SUMMARY.md\", there should be at least one");
-        return Ok(());
-    }
+    let (file_md, file_mdx) = tokio::join!(file_md, file_mdx);
 
-    println!("Langs: {}", collection.summaries.len());
+    let file_md = map_not_found_file(file_md)?;
+    let file_mdx = map_not_found_file(file_mdx)?;
 
-    println!("\nChecking files not linked...\n");
+    let mut file_content = String::new();
 
-    let used_files = &collection.parser.all_files;
-    let all_files = &collection.all_files;
+    if let Some(mut file_md) = file_md {
+        if file_mdx.is_some() {
+            return Err(anyhow!("The entry {} is duplicated", file_base.display()));
+        }
 
-    for unused_file in all_files.difference(used_files) {
-        let relative_path = unused_file.strip_prefix(&dir)?;
-        log::warn!("- {}", relative_path.display());
+        file_md.read_to_string(&mut file_content).await?;
+        markdown::to_mdast(
+            &file_content,
+            &markdown::ParseOptions {
+                constructs: markdown::Constructs {
+                    frontmatter: true,
+                    ..markdown::Constructs::gfm()
+                },
+                ..markdown::ParseOptions::gfm()
+            },
+        )
+        .map_err(|err| anyhow!("{err}"))
+    } else if let Some(mut file_mdx) = file_mdx {
+        file_mdx.read_to_string(&mut file_content).await?;
+        markdown::to_mdast(
+            &file_content,
+            &markdown::ParseOptions {
+                constructs: markdown::Constructs {
+                    frontmatter: true,
+                    ..markdown::Constructs::mdx()
+                },
+                ..Default::default()
+            },
+        )
+        .map_err(|err| anyhow!("{err}"))
+    } else {
+        Err(anyhow!("No file for {}", file_base.display()))
     }
+}
 
-    for file in used_files {
-        let relative_path = file.strip_prefix(&src_dir)?;
-        println!("Compiling /{}...", relative_path.display());
-
-        let Some(summary) = get_summary(file, &collection.summaries) else {
-            log::error!("No summary for this file");
-            continue;
-        };
-
-        println!("Using summary: {}", summary.dir.display());
-
-        let file_parsed = {
-            let dir = file.parent().expect("Cannot get dirname");
-            let file_name = file.file_stem().expect("Cannot get filename");
-            let file_base = dir.join(file_name);
-
-            let file_md = fs::File::open(file_base.with_extension("md"));
-            let file_mdx = fs::File::open(file_base.with_extension("mdx"));
-
-            let (file_md, file_mdx) = tokio::join!(file_md, file_mdx);
-
-            let file_md = map_not_found_file(file_md)?;
-            let file_mdx = map_not_found_file(file_mdx)?;
-
-            let mut file_content = String::new();
-
-            if let Some(mut file_md) = file_md {
-                if file_mdx.is_some() {
-                    return Err(anyhow!("The entry {} is duplicated", file_base.display()));
-                }
-
-                file_md.read_to_string(&mut file_content).await?;
-                markdown::to_mdast(
-                    &file_content,
-                    &markdown::ParseOptions {
-                        constructs: markdown::Constructs {
-                            frontmatter: true,
-                            ..markdown::Constructs::gfm()
-                        },
-                        ..markdown::ParseOptions::gfm()
-                    },
-                )
-                .map_err(|err| anyhow!("{err}"))?
-            } else if let Some(mut file_mdx) = file_mdx {
-                file_mdx.read_to_string(&mut file_content).await?;
-                markdown::to_mdast(
-                    &file_content,
-                    &markdown::ParseOptions {
-                        constructs: markdown::Constructs {
-                            frontmatter: true,
-                            ..markdown::Constructs::mdx()
-                        },
-                        ..Default::default()
-                    },
-                )
-                .map_err(|err| anyhow!("{err}"))?
-            } else {
-                return Err(anyhow!("No file for {}", file_base.display()));
-            }
-        };
+/// Finds the first chapter linked by a summary, in the order of the summary
+fn first_chapter<'a>(src_dir: &Path, summary: &'a Summary) -> Option<(&'a str, PathBuf)> {
+    fn find<'a>(
+        src_dir: &Path,
+        dir: &Path,
+        nodes: impl IntoIterator<Item = &'a TreeNode>,
+    ) -> Option<(&'a str, PathBuf)> {
+        nodes.into_iter().find_map(|node| {
+            node.href
+                .as_deref()
+                .and_then(|href| resolve_link(src_dir, dir, href))
+                .map(|file| (node.title.as_str(), file))
+                .or_else(|| find(src_dir, dir, &node.children))
+        })
+    }
 
-        // println!("{file_parsed:#?}");
+    find(src_dir, &summary.dir, summary.entries())
+}
 
-        let html = to_html(file_parsed);
+fn is_chapter(file: &Path) -> bool {
+    matches!(
+        file.extension().and_then(|ext| ext.to_str()),
+        Some("md" | "mdx")
+    )
+}
 
-        // println!("{html}");
+async fn copy_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).await?;
     }
 
-    Ok(())
+    fs::copy(from, to)
+        .await
+        .map(|_| ())
+        .map_err(|err| anyhow!("Cannot copy {from:?}.\n  Cause: {err}"))
+}
+
+async fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    fs::write(path, contents)
+        .await
+        .map_err(|err| anyhow!("Cannot write {path:?}.\n  Cause: {err}"))
 }
 
 fn get_summary<'a>(
-    file: &'a PathBuf,
+    file: &'a Path,
     summaries: &'a HashMap<PathBuf, Summary>,
 ) -> Option<&'a Summary> {
     let mut best_summary = (usize::MAX, None);
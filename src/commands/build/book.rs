@@ -0,0 +1,623 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use tokio::fs;
+
+use crate::models::Config;
+
+use super::render::Renderer;
+use super::theme::Theme;
+use super::{
+    chapter_base, chapter_with_extension, same_chapter, Diagnostics, Summary, SummaryError,
+};
+use super::{
+    collect_summaries, copy_file, first_chapter, get_summary, is_chapter, read_chapter, write_file,
+};
+
+/// Content of the 404 page when the book does not have one
+const DEFAULT_404: &str = "# Document not found (404)
+
+This URL is invalid, sorry. Please use the navigation to continue.
+";
+
+/// Number of pages and files written by [`Book::rebuild`]
+#[derive(Debug, Default)]
+pub struct Rebuild {
+    pub chapters: usize,
+    pub files: usize,
+}
+
+/// A book loaded from disk, with everything needed to build its pages
+pub struct Book {
+    /// Root directory of the book, where `book.toml` is
+    pub dir: PathBuf,
+    pub config: Config,
+    pub src_dir: PathBuf,
+    pub build_dir: PathBuf,
+    pub theme_dir: PathBuf,
+    theme: Theme,
+    /// Summaries of the book, by the directory that contains them
+    summaries: HashMap<PathBuf, Summary>,
+    /// Chapters linked by the summaries
+    chapters: HashSet<PathBuf>,
+    /// Files of the source directory, except the summaries
+    files: HashSet<PathBuf>,
+}
+
+impl Book {
+    /// Loads the book at `dir`, which must be canonical, to be built in
+    /// `out_dir` or the `build-dir` of the configuration.
+    ///
+    /// The build directory and the missing chapters are created
+    pub async fn load(dir: &Path, out_dir: Option<&Path>) -> Result<Self> {
+        let mut book = Self::open(dir, out_dir).await?;
+
+        fs::create_dir_all(&book.build_dir).await?;
+        book.build_dir = book.build_dir.canonicalize()?;
+
+        book.create_missing_chapters().await?;
+
+        Ok(book)
+    }
+
+    /// Loads the book at `dir`, which must be canonical, without writing
+    /// anything, for the commands that only read it
+    pub async fn open(dir: &Path, out_dir: Option<&Path>) -> Result<Self> {
+        let config = Config::from_disk(dir.join("book.toml"))?;
+        let html_config = config.html_config();
+
+        let build_dir = config.build_dir(dir, out_dir);
+
+        let src_dir = dir.join(&config.book.src);
+
+        let theme_dir = dir.join(html_config.theme.as_deref().unwrap_or("theme"));
+        if html_config.theme.is_some() && !theme_dir.is_dir() {
+            log::warn!("The theme directory {theme_dir:?} does not exist");
+        }
+
+        let theme = Theme::load(&theme_dir).await?;
+
+        let collection = collect_summaries(&src_dir).await?;
+
+        let diagnostics = Diagnostics(collection.parser.diagnostics);
+        let strict = config.build.as_ref().is_some_and(|build| build.strict);
+
+        if strict && !diagnostics.is_empty() {
+            return Err(SummaryError::Diagnostics(diagnostics).into());
+        }
+
+        for diagnostic in &diagnostics.0 {
+            eprintln!("{diagnostic}\n");
+        }
+
+        let summaries = collection.summaries;
+        let chapters = collection.parser.all_files;
+        let files = collection.all_files;
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            config,
+            src_dir,
+            build_dir,
+            theme_dir,
+            theme,
+            summaries,
+            chapters,
+            files,
+        })
+    }
+
+    /// Builds every page of the book and copies its static files
+    pub async fn build(&self) -> Result<()> {
+        if self.summaries.is_empty() {
+            eprintln!("No \"SUMMARY.md\", there should be at least one");
+            return Ok(());
+        }
+
+        log::info!("Langs: {}", self.summaries.len());
+
+        let unused_files = self.files.iter().filter(|file| {
+            is_chapter(file)
+                && !self
+                    .chapters
+                    .iter()
+                    .any(|chapter| same_chapter(chapter, file))
+        });
+
+        for unused_file in unused_files {
+            let relative_path = unused_file.strip_prefix(&self.dir)?;
+            log::warn!("File not linked: {}", relative_path.display());
+        }
+
+        let renderer = self.renderer();
+        let mut written_files = HashSet::new();
+
+        for file in &self.chapters {
+            let output_path = self.output_path(file)?;
+
+            // The same chapter can be linked more than once
+            if !written_files.insert(output_path) {
+                continue;
+            }
+
+            self.render_chapter(&renderer, file).await?;
+        }
+
+        self.theme.write_assets(&self.build_dir).await?;
+
+        for asset in self.additional_assets() {
+            let relative_path = asset.strip_prefix(&self.dir)?;
+
+            log::info!("Copying {}...", relative_path.display());
+
+            copy_file(&asset, &self.build_dir.join(relative_path)).await?;
+        }
+
+        for file in self.files.iter().filter(|file| !is_chapter(file)) {
+            self.copy_static_file(file).await?;
+        }
+
+        if !self.has_index_chapter() {
+            self.render_index(&renderer).await?;
+        }
+
+        self.render_404(&renderer).await?;
+
+        Ok(())
+    }
+
+    /// Builds again the pages and files affected by changes to `paths`,
+    /// which must not change the structure of the book
+    pub async fn rebuild(&self, paths: &[PathBuf]) -> Result<Rebuild> {
+        let renderer = self.renderer();
+        let mut rebuild = Rebuild::default();
+
+        for path in paths {
+            if !path.starts_with(&self.src_dir) {
+                if self.additional_assets().any(|asset| asset == *path) {
+                    let relative_path = path.strip_prefix(&self.dir)?;
+                    copy_file(path, &self.build_dir.join(relative_path)).await?;
+                    rebuild.files += 1;
+                }
+                continue;
+            }
+
+            if !is_chapter(path) {
+                if path.is_file() {
+                    self.copy_static_file(path).await?;
+                    rebuild.files += 1;
+                }
+                continue;
+            }
+
+            let is_404 = same_chapter(path, &self.page_404());
+
+            if is_404 {
+                self.render_404(&renderer).await?;
+                rebuild.chapters += 1;
+            }
+
+            let Some(chapter) = self
+                .chapters
+                .iter()
+                .find(|chapter| same_chapter(chapter, path))
+            else {
+                if !is_404 {
+                    log::info!("{} is not linked by any summary", path.display());
+                }
+                continue;
+            };
+
+            self.render_chapter(&renderer, chapter).await?;
+            rebuild.chapters += 1;
+
+            // The index page is a copy of the first chapter
+            let is_index = self
+                .index_chapter()
+                .is_some_and(|index| same_chapter(&index, path));
+
+            if is_index && !self.has_index_chapter() {
+                self.render_index(&renderer).await?;
+            }
+        }
+
+        Ok(rebuild)
+    }
+
+    /// Chapters linked by the summaries with their titles, sorted by path.
+    ///
+    /// A chapter linked more than once is only returned once
+    pub fn chapters(&self) -> Vec<(&str, &Path)> {
+        let mut chapters = self
+            .chapters
+            .iter()
+            .filter_map(|file| {
+                let summary = get_summary(file, &self.summaries)?;
+                let chapter = summary.find_chapter(&self.src_dir, file)?;
+
+                Some((chapter.title.as_str(), file.as_path()))
+            })
+            .collect::<Vec<_>>();
+
+        // Links can omit the extension
+        chapters.sort_by_key(|(_, file)| chapter_base(file));
+        chapters.dedup_by(|(_, a), (_, b)| same_chapter(a, b));
+
+        chapters
+    }
+
+    /// Creates the chapters linked by the summaries that do not exist, with
+    /// their title as heading, if `create-missing` is enabled
+    async fn create_missing_chapters(&mut self) -> Result<()> {
+        let create_missing = self.config.build.clone().unwrap_or_default().create_missing;
+
+        // Chapters are found without their extension, like `read_chapter`
+        // does
+        let missing = self
+            .chapters()
+            .into_iter()
+            .filter(|(_, file)| {
+                !chapter_with_extension(file, "md").is_file()
+                    && !chapter_with_extension(file, "mdx").is_file()
+            })
+            .map(|(title, file)| {
+                let extension = match file.extension().and_then(|ext| ext.to_str()) {
+                    Some("mdx") => "mdx",
+                    _ => "md",
+                };
+
+                (title.to_string(), chapter_with_extension(file, extension))
+            })
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        if !create_missing {
+            let missing = missing
+                .iter()
+                .map(|(_, file)| {
+                    file.strip_prefix(&self.dir)
+                        .unwrap_or(file)
+                        .display()
+                        .to_string()
+                })
+                .collect::<Vec<_>>();
+
+            return Err(anyhow!(
+                "Missing chapters:\n  {}\nCreate them or enable build.create-missing",
+                missing.join("\n  ")
+            ));
+        }
+
+        for (title, file) in missing {
+            let relative_path = file.strip_prefix(&self.dir)?;
+
+            log::info!("Creating missing chapter {}...", relative_path.display());
+
+            write_file(&file, format!("# {title}\n")).await?;
+            self.files.insert(file);
+        }
+
+        Ok(())
+    }
+
+    /// Copies a file of the source directory to the build directory
+    pub async fn copy_static_file(&self, file: &Path) -> Result<()> {
+        let relative_path = file.strip_prefix(&self.src_dir)?;
+
+        log::info!("Copying /{}...", relative_path.display());
+
+        copy_file(file, &self.build_dir.join(relative_path)).await
+    }
+
+    /// Whether a change to `path` changes the structure of the book, so it has
+    /// to be loaded again
+    pub fn needs_reload(&self, path: &Path) -> bool {
+        let extra_watch_dirs = self
+            .config
+            .build
+            .iter()
+            .flat_map(|build| &build.extra_watch_dirs);
+
+        path == self.dir.join("book.toml")
+            || path.file_name().is_some_and(|name| name == "SUMMARY.md")
+            || path.starts_with(&self.theme_dir)
+            || extra_watch_dirs
+                .into_iter()
+                .any(|dir| path.starts_with(self.dir.join(dir)))
+            // New chapters and files are found when the book is loaded
+            || path.starts_with(&self.src_dir) && !self.files.contains(path) && path.is_file()
+    }
+
+    /// Paths of the watched directories and files, and whether they are
+    /// watched recursively
+    pub fn watched_paths(&self) -> Vec<(PathBuf, bool)> {
+        let extra_watch_dirs = self
+            .config
+            .build
+            .iter()
+            .flat_map(|build| &build.extra_watch_dirs)
+            .map(|dir| self.dir.join(dir));
+
+        // The root is not recursive to not watch the build directory, but
+        // `book.toml` can be replaced instead of modified when it is saved
+        let mut paths = vec![(self.dir.clone(), false), (self.src_dir.clone(), true)];
+
+        if self.theme_dir.is_dir() {
+            paths.push((self.theme_dir.clone(), true));
+        }
+
+        for dir in extra_watch_dirs {
+            if dir.is_dir() {
+                paths.push((dir, true));
+            } else {
+                log::warn!("The directory to watch {dir:?} does not exist");
+            }
+        }
+
+        paths
+    }
+
+    fn additional_assets(&self) -> impl Iterator<Item = PathBuf> + '_ {
+        let html_config = self.config.html_config();
+
+        html_config
+            .additional_css
+            .into_iter()
+            .chain(html_config.additional_js)
+            .flatten()
+            .map(|asset| self.dir.join(asset))
+    }
+
+    fn renderer(&self) -> Renderer<'_> {
+        Renderer::new(&self.config, &self.src_dir, &self.theme)
+    }
+
+    fn output_path(&self, file: &Path) -> Result<PathBuf> {
+        let relative_path = file.strip_prefix(&self.src_dir)?;
+
+        Ok(chapter_with_extension(
+            &self.build_dir.join(relative_path),
+            "html",
+        ))
+    }
+
+    async fn render_chapter(&self, renderer: &Renderer<'_>, file: &Path) -> Result<()> {
+        let relative_path = file.strip_prefix(&self.src_dir)?;
+
+        log::info!("Compiling /{}...", relative_path.display());
+
+        let Some(summary) = get_summary(file, &self.summaries) else {
+            log::error!("No summary for {}", relative_path.display());
+            return Ok(());
+        };
+
+        let html = renderer
+            .render_chapter(summary, file, relative_path)
+            .await?;
+
+        write_file(&self.output_path(file)?, html).await
+    }
+
+    /// Whether a chapter is built as `index.html`
+    fn has_index_chapter(&self) -> bool {
+        let index = self.build_dir.join("index.html");
+
+        self.chapters.iter().any(|chapter| {
+            self.output_path(chapter)
+                .is_ok_and(|output| output == index)
+        })
+    }
+
+    /// Chapter used as index page when no chapter is built as `index.html`
+    fn index_chapter(&self) -> Option<PathBuf> {
+        let summary = self.summaries.get(&self.src_dir)?;
+
+        first_chapter(&self.src_dir, summary).map(|(_, file)| file)
+    }
+
+    async fn render_index(&self, renderer: &Renderer<'_>) -> Result<()> {
+        let summary = self.summaries.get(&self.src_dir);
+        let index = summary.and_then(|summary| first_chapter(&self.src_dir, summary));
+
+        let (Some(summary), Some((title, file))) = (summary, index) else {
+            log::warn!("There is no chapter to use as index page");
+            return Ok(());
+        };
+
+        log::info!("Using \"{title}\" as index page");
+
+        let html = renderer
+            .render_chapter(summary, &file, Path::new("index.html"))
+            .await?;
+
+        write_file(&self.build_dir.join("index.html"), html).await
+    }
+
+    /// Source of the page shown for missing files, which can be outside the
+    /// summary
+    fn page_404(&self) -> PathBuf {
+        let input_404 = self.config.html_config().input_404;
+
+        self.src_dir.join(input_404.as_deref().unwrap_or("404.md"))
+    }
+
+    async fn render_404(&self, renderer: &Renderer<'_>) -> Result<()> {
+        let file = self.page_404();
+
+        let Some(summary) = get_summary(&file, &self.summaries) else {
+            log::warn!("There is no summary to render the 404 page");
+            return Ok(());
+        };
+
+        let node = if file.is_file() {
+            read_chapter(&file).await?
+        } else {
+            markdown::to_mdast(DEFAULT_404, &Default::default())
+                .expect("The default 404 page is valid markdown")
+        };
+
+        let html = renderer.render_404(summary, &file, node)?;
+
+        write_file(&self.build_dir.join("404.html"), html).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// Creates a book whose summary links chapters that do not exist
+    fn setup(book_toml: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("book.toml"), book_toml).unwrap();
+        fs::write(
+            dir.path().join("src/SUMMARY.md"),
+            "- [Intro](intro.md)
+- [Getting started](getting-started/hello-world)
+    - [Draft]()
+",
+        )
+        .unwrap();
+        fs::write(dir.path().join("src/intro.md"), "# Introduction\n").unwrap();
+
+        dir
+    }
+
+    #[tokio::test]
+    async fn book_is_written_to_the_build_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let image = [0x89, b'P', b'N', b'G', 0xff];
+
+        fs::create_dir_all(root.join("src/guide/images")).unwrap();
+        fs::write(root.join("book.toml"), "[book]\ntitle = \"Book\"\n").unwrap();
+        fs::write(
+            root.join("src/SUMMARY.md"),
+            "- [Intro](intro.md)\n- [Guide](guide/chapter.md)\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/intro.md"), "# Introduction\n\nWelcome\n").unwrap();
+        fs::write(
+            root.join("src/guide/chapter.md"),
+            "# Chapter\n\n![Logo](images/logo.png)\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/guide/images/logo.png"), image).unwrap();
+
+        let book = Book::load(&root, None).await.unwrap();
+        book.build().await.unwrap();
+
+        let read = |path| fs::read_to_string(root.join("book").join(path)).unwrap();
+
+        let intro = read("intro.html");
+        assert!(intro.contains("<h1"));
+        assert!(intro.contains("Welcome"));
+        assert!(intro.contains("<title>1. Intro - Book</title>"));
+
+        let chapter = read("guide/chapter.html");
+        assert!(chapter.contains(r#"src="images/logo.png""#));
+        assert!(chapter.contains(r#"href="../book.css""#));
+
+        // The first chapter is the index page
+        assert!(read("index.html").contains("Welcome"));
+        assert!(read("404.html").contains("Document not found"));
+
+        for asset in ["book.css", "book.js", "highlight.css"] {
+            assert!(root.join("book").join(asset).is_file(), "{asset}");
+        }
+        assert_eq!(
+            fs::read(root.join("book/guide/images/logo.png")).unwrap(),
+            image
+        );
+        assert!(!root.join("book/SUMMARY.html").exists());
+    }
+
+    #[tokio::test]
+    async fn page_404_is_rebuilt() {
+        let dir = setup("[book]\ntitle = \"Book\"\n");
+        let root = dir.path().canonicalize().unwrap();
+        let page_404 = root.join("src/404.md");
+
+        fs::write(&page_404, "# Lost\n").unwrap();
+
+        let book = Book::load(&root, None).await.unwrap();
+        book.build().await.unwrap();
+
+        let read_404 = || fs::read_to_string(root.join("book/404.html")).unwrap();
+        assert!(read_404().contains("Lost"));
+
+        fs::write(&page_404, "# Gone\n").unwrap();
+        let rebuild = book.rebuild(&[page_404]).await.unwrap();
+
+        assert_eq!(rebuild.chapters, 1);
+        assert!(read_404().contains("Gone"));
+        assert!(!read_404().contains("Lost"));
+    }
+
+    #[tokio::test]
+    async fn missing_chapters_are_created() {
+        let dir = setup("[book]\ntitle = \"Book\"\n");
+        let root = dir.path().canonicalize().unwrap();
+
+        Book::load(&root, None).await.unwrap();
+
+        let read = |path| fs::read_to_string(root.join(path)).unwrap();
+
+        assert_eq!(
+            read("src/getting-started/hello-world.md"),
+            "# Getting started\n"
+        );
+        assert_eq!(read("src/intro.md"), "# Introduction\n");
+        assert_eq!(fs::read_dir(root.join("src")).unwrap().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn chapters_with_dots_keep_their_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("book.toml"), "[book]\ntitle = \"Book\"\n").unwrap();
+        fs::write(
+            root.join("src/SUMMARY.md"),
+            "- [Chapter 1.1](chapter-1.1.md)\n- [Chapter 1.2](chapter-1.2)\n",
+        )
+        .unwrap();
+
+        let book = Book::load(&root, None).await.unwrap();
+        book.build().await.unwrap();
+
+        let read = |path| fs::read_to_string(root.join(path)).unwrap();
+
+        assert_eq!(read("src/chapter-1.1.md"), "# Chapter 1.1\n");
+        assert_eq!(read("src/chapter-1.2.md"), "# Chapter 1.2\n");
+        assert!(!root.join("src/chapter-1.md").exists());
+
+        assert!(read("book/chapter-1.1.html").contains("Chapter 1.1"));
+        assert!(read("book/chapter-1.2.html").contains("Chapter 1.2"));
+        assert!(!root.join("book/chapter-1.html").exists());
+    }
+
+    #[tokio::test]
+    async fn missing_chapters_are_reported_if_not_created() {
+        let dir = setup("[book]\ntitle = \"Book\"\n\n[build]\ncreate-missing = false\n");
+        let root = dir.path().canonicalize().unwrap();
+
+        let Err(err) = Book::load(&root, None).await else {
+            panic!("The book loaded without its chapters");
+        };
+
+        assert!(err
+            .to_string()
+            .contains("Missing chapters:\n  src/getting-started/hello-world.md\n"));
+        assert!(!root.join("src/getting-started").exists());
+    }
+}
@@ -17,12 +17,12 @@ pub async fn collect_summaries<'a>(
     path: &'a PathBuf,
 ) -> Result<SummaryCollection<'a>, SummaryError> {
     let mut collection = SummaryCollection {
-        parser: SummaryParser::new(&path),
+        parser: SummaryParser::new(path),
         summaries: HashMap::new(),
         all_files: HashSet::new(),
     };
 
-    explore_dir(&path, &mut collection).await?;
+    explore_dir(path, &mut collection).await?;
 
     Ok(collection)
 }
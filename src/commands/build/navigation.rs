@@ -0,0 +1,515 @@
+use std::fmt::Write;
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::slice::Iter;
+
+use serde::Serialize;
+
+use crate::models::preprocessors_config::HtmlFold;
+
+use super::to_html::{escape_attr, escape_text, Heading};
+use super::{chapter_with_extension, resolve_link, Summary, TreeNode, TreeNodeKind};
+
+/// Navigation elements of the page of `file`, generated from its summary
+pub struct Navigation<'a> {
+    pub src_dir: &'a Path,
+    pub summary: &'a Summary,
+    pub file: &'a Path,
+    pub path_to_root: &'a str,
+    /// Shows the numbers of the chapters before their titles
+    pub section_labels: bool,
+}
+
+/// Entry of the summary exposed to the page template
+#[derive(Debug, Serialize)]
+pub struct NavLink {
+    pub title: String,
+    /// Link relative to the current page, if the entry has a page
+    pub link: Option<String>,
+}
+
+/// Heading of the current page exposed to the template, with the headings
+/// nested under it
+#[derive(Debug, Serialize)]
+pub struct TocEntry {
+    pub title: String,
+    pub id: String,
+    pub children: Vec<TocEntry>,
+}
+
+impl Navigation<'_> {
+    /// Renders the table of contents of the summary
+    pub fn sidebar(&self, fold: &HtmlFold) -> String {
+        let mut html = String::new();
+
+        _ = write!(html, r#"<ol class="chapter">"#);
+        for node in self.summary.entries() {
+            self.render_item(&mut html, node, fold, 1);
+        }
+        _ = write!(html, "</ol>");
+
+        html
+    }
+
+    /// Previous and next chapters, in the order they appear in the summary
+    pub fn previous_next(&self) -> (Option<NavLink>, Option<NavLink>) {
+        let mut chapters: Vec<&TreeNode> = Vec::new();
+
+        fn flatten<'a>(
+            nav: &Navigation,
+            nodes: impl IntoIterator<Item = &'a TreeNode>,
+            chapters: &mut Vec<&'a TreeNode>,
+        ) {
+            for node in nodes {
+                let is_new = !chapters.iter().any(|chapter| nav.same_page(chapter, node));
+
+                if nav.local_page(node).is_some() && is_new {
+                    chapters.push(node);
+                }
+
+                flatten(nav, &node.children, chapters);
+            }
+        }
+
+        flatten(self, self.summary.entries(), &mut chapters);
+
+        let Some(current) = chapters
+            .iter()
+            .position(|chapter| chapter.links_to(self.src_dir, &self.summary.dir, self.file))
+        else {
+            return (None, None);
+        };
+
+        // The titles are labelled like the ones of the sidebar and the page
+        let labelled_link = |chapter: &&TreeNode| NavLink {
+            title: labelled_title(chapter, self.section_labels),
+            ..self.nav_link(chapter)
+        };
+
+        let previous = current
+            .checked_sub(1)
+            .and_then(|index| chapters.get(index))
+            .map(labelled_link);
+        let next = chapters.get(current + 1).map(labelled_link);
+
+        (previous, next)
+    }
+
+    /// Entries from the top of the summary to the current chapter, the latter
+    /// without link
+    pub fn breadcrumbs(&self) -> Vec<NavLink> {
+        fn find<'a>(
+            nav: &Navigation,
+            nodes: impl IntoIterator<Item = &'a TreeNode>,
+            trail: &mut Vec<&'a TreeNode>,
+        ) -> bool {
+            for node in nodes {
+                trail.push(node);
+
+                if node.links_to(nav.src_dir, &nav.summary.dir, nav.file)
+                    || find(nav, &node.children, trail)
+                {
+                    return true;
+                }
+
+                trail.pop();
+            }
+
+            false
+        }
+
+        let mut trail = Vec::new();
+
+        find(self, self.summary.entries(), &mut trail);
+
+        let current = trail.pop();
+
+        trail
+            .into_iter()
+            .map(|node| self.nav_link(node))
+            .chain(current.map(|node| NavLink {
+                title: node.title.clone(),
+                link: None,
+            }))
+            .collect()
+    }
+
+    fn render_item(&self, html: &mut String, node: &TreeNode, fold: &HtmlFold, depth: u16) {
+        let mut title = escape_text(&node.title);
+
+        if let (true, Some(number)) = (self.section_labels, &node.number) {
+            title = format!(r#"<strong aria-hidden="true">{number}</strong> {title}"#).into();
+        }
+
+        match node.kind {
+            TreeNodeKind::PartTitle => {
+                _ = write!(html, r#"<li class="part-title">{title}</li>"#);
+                return;
+            }
+            TreeNodeKind::Separator => {
+                _ = write!(html, r#"<li class="spacer" role="separator"></li>"#);
+                return;
+            }
+            TreeNodeKind::Chapter | TreeNodeKind::Draft => {}
+        }
+
+        let class = if node.children.is_empty() {
+            "chapter-item"
+        } else if self.is_expanded(node, fold, depth) {
+            "chapter-item expanded"
+        } else {
+            "chapter-item collapsed"
+        };
+        let draft = if node.kind == TreeNodeKind::Draft {
+            " draft"
+        } else {
+            ""
+        };
+
+        _ = write!(
+            html,
+            r#"<li class="{class}{draft}"><div class="chapter-link">"#
+        );
+
+        match &node.href {
+            Some(href) => {
+                let active = if node.links_to(self.src_dir, &self.summary.dir, self.file) {
+                    r#" class="active" aria-current="page""#
+                } else {
+                    ""
+                };
+                let href = self.page_href(href);
+                let href = escape_attr(&href);
+
+                _ = write!(html, r#"<a href="{href}"{active}>{title}</a>"#);
+            }
+            None => _ = write!(html, r#"<span class="chapter-title">{title}</span>"#),
+        }
+
+        if !node.children.is_empty() {
+            _ = write!(
+                html,
+                r#"<a class="toggle" role="button" aria-label="Toggle section">❱</a>"#
+            );
+        }
+
+        _ = write!(html, "</div>");
+
+        if !node.children.is_empty() {
+            _ = write!(html, r#"<ol class="section">"#);
+            for child in &node.children {
+                self.render_item(html, child, fold, depth + 1);
+            }
+            _ = write!(html, "</ol>");
+        }
+
+        _ = write!(html, "</li>");
+    }
+
+    /// Sections are folded from the configured level, except the ones that
+    /// contain the current chapter
+    fn is_expanded(&self, node: &TreeNode, fold: &HtmlFold, depth: u16) -> bool {
+        !fold.enable
+            || depth - 1 < fold.level.unwrap_or_default()
+            || node.contains(self.src_dir, &self.summary.dir, self.file)
+    }
+
+    fn nav_link(&self, node: &TreeNode) -> NavLink {
+        NavLink {
+            title: node.title.clone(),
+            link: node.href.as_deref().map(|href| self.page_href(href)),
+        }
+    }
+
+    /// Chapter of the book linked by an entry, relative to the source directory
+    fn local_page(&self, node: &TreeNode) -> Option<PathBuf> {
+        self.local_href(node.href.as_deref()?)
+    }
+
+    fn local_href(&self, href: &str) -> Option<PathBuf> {
+        let file = resolve_link(self.src_dir, &self.summary.dir, href)?;
+
+        file.strip_prefix(self.src_dir).ok().map(Path::to_path_buf)
+    }
+
+    fn same_page(&self, a: &TreeNode, b: &TreeNode) -> bool {
+        match (self.local_page(a), self.local_page(b)) {
+            (Some(a), Some(b)) => url_path(&a) == url_path(&b),
+            _ => false,
+        }
+    }
+
+    /// Resolves a link of the summary to the page it points to, relative to the
+    /// current page
+    fn page_href(&self, href: &str) -> String {
+        match self.local_href(href) {
+            Some(page) => format!("{}{}", self.path_to_root, url_path(&page)),
+            None => href.to_string(),
+        }
+    }
+}
+
+/// Title of an entry of the summary, after its number if `section_labels` is
+/// enabled
+pub fn labelled_title(node: &TreeNode, section_labels: bool) -> String {
+    match &node.number {
+        Some(number) if section_labels => format!("{number} {}", node.title),
+        _ => node.title.clone(),
+    }
+}
+
+/// Path of the page generated for a chapter, using `/` as separator
+pub fn url_path(chapter: &Path) -> String {
+    chapter_with_extension(chapter, "html")
+        .iter()
+        .map(|component| component.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Tree of the headings of a page, empty when there is nothing to navigate.
+///
+/// Each heading is nested under the previous one with a lower depth, so
+/// skipped levels do not leave empty entries
+pub fn page_toc(headings: &[Heading]) -> Vec<TocEntry> {
+    fn build(headings: &mut Peekable<Iter<Heading>>, depth: u8) -> Vec<TocEntry> {
+        let mut entries = Vec::new();
+
+        while let Some(heading) = headings.next_if(|heading| heading.depth >= depth) {
+            entries.push(TocEntry {
+                title: heading.title.clone(),
+                id: heading.id.clone(),
+                children: build(headings, heading.depth + 1),
+            });
+        }
+
+        entries
+    }
+
+    if headings.len() < 2 {
+        return Vec::new();
+    }
+
+    build(&mut headings.iter().peekable(), 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::commands::build::SummaryParser;
+
+    const SUMMARY: &str = "[Intro](intro.md)
+
+# Guide
+
+- [Start](guide/start.md)
+    - [Install](guide/install.md)
+        - [Linux](guide/linux.md)
+- [Draft]()
+- [External](https://example.com)
+
+---
+
+- [Start again](guide/start)
+
+[Thanks](thanks.md)
+";
+
+    /// Parses a summary in the source directory of a book
+    async fn summary(summary: &str) -> (TempDir, Summary) {
+        let dir = tempfile::tempdir().unwrap();
+        let src_dir = dir.path().to_path_buf();
+        std::fs::write(src_dir.join("SUMMARY.md"), summary).unwrap();
+
+        let summary = SummaryParser::new(&src_dir)
+            .parse_dir(&src_dir)
+            .await
+            .unwrap();
+
+        (dir, summary)
+    }
+
+    /// Calls `f` with the navigation of the page of `chapter`, relative to
+    /// the source directory
+    fn navigate<T>(
+        summary: &Summary,
+        chapter: &str,
+        section_labels: bool,
+        f: impl FnOnce(&Navigation) -> T,
+    ) -> T {
+        let file = summary.dir.join(chapter);
+        let path_to_root = "../".repeat(Path::new(chapter).components().count() - 1);
+
+        f(&Navigation {
+            src_dir: &summary.dir,
+            summary,
+            file: &file,
+            path_to_root: &path_to_root,
+            section_labels,
+        })
+    }
+
+    fn sidebar(summary: &Summary, chapter: &str, fold: &HtmlFold, section_labels: bool) -> String {
+        navigate(summary, chapter, section_labels, |nav| nav.sidebar(fold))
+    }
+
+    /// Titles and links of the previous and next chapters of `chapter`
+    fn previous_next(summary: &Summary, chapter: &str) -> [Option<(String, Option<String>)>; 2] {
+        let (previous, next) = navigate(summary, chapter, false, |nav| nav.previous_next());
+
+        [previous, next].map(|link| link.map(|link| (link.title, link.link)))
+    }
+
+    fn page(title: &str, link: &str) -> Option<(String, Option<String>)> {
+        Some((title.to_string(), Some(link.to_string())))
+    }
+
+    #[tokio::test]
+    async fn sidebar_has_every_kind_of_entry() {
+        let (_dir, summary) = summary(SUMMARY).await;
+        let html = sidebar(&summary, "intro.md", &HtmlFold::default(), true);
+
+        assert!(html.starts_with(r#"<ol class="chapter"><li class="chapter-item"><div class="chapter-link"><a href="intro.html" class="active" aria-current="page">Intro</a></div></li>"#));
+        assert!(html.contains(r#"<li class="part-title">Guide</li>"#));
+        assert!(html.contains(r#"<li class="spacer" role="separator"></li>"#));
+        assert!(html.contains(r#"<li class="chapter-item draft"><div class="chapter-link"><span class="chapter-title"><strong aria-hidden="true">2.</strong> Draft</span></div></li>"#));
+        assert!(html.contains(
+            r#"<a href="https://example.com"><strong aria-hidden="true">3.</strong> External</a>"#
+        ));
+        assert!(html.contains(r#"<a href="thanks.html">Thanks</a>"#));
+        assert_eq!(html.matches("aria-current").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn sidebar_links_are_relative_to_the_page() {
+        let (_dir, summary) = summary(SUMMARY).await;
+        let html = sidebar(&summary, "guide/install.md", &HtmlFold::default(), true);
+
+        assert!(html.contains(r#"<a href="../intro.html">Intro</a>"#));
+        assert!(html.contains(r#"<a href="../guide/install.html" class="active" aria-current="page"><strong aria-hidden="true">1.1.</strong> Install</a>"#));
+        assert!(html.contains(
+            r#"<a href="../guide/linux.html"><strong aria-hidden="true">1.1.1.</strong> Linux</a>"#
+        ));
+    }
+
+    #[tokio::test]
+    async fn section_labels_can_be_hidden() {
+        let (_dir, summary) = summary(SUMMARY).await;
+        let html = sidebar(&summary, "intro.md", &HtmlFold::default(), false);
+
+        assert!(!html.contains("<strong"));
+        assert!(html.contains(r#"<a href="guide/install.html">Install</a>"#));
+    }
+
+    #[tokio::test]
+    async fn sections_are_folded_from_the_configured_level() {
+        let (_dir, summary) = summary(SUMMARY).await;
+        let item = |class: &str, title: &str, href: &str| {
+            format!(
+                r#"<li class="chapter-item {class}"><div class="chapter-link"><a href="{href}">{title}</a>"#
+            )
+        };
+
+        let html = sidebar(&summary, "thanks.md", &HtmlFold::default(), false);
+        assert!(html.contains(&item("expanded", "Start", "guide/start.html")));
+        assert!(html.contains(&item("expanded", "Install", "guide/install.html")));
+
+        let fold = HtmlFold {
+            enable: true,
+            level: None,
+        };
+        let html = sidebar(&summary, "thanks.md", &fold, false);
+        assert!(html.contains(&item("collapsed", "Start", "guide/start.html")));
+        assert!(html.contains(&item("collapsed", "Install", "guide/install.html")));
+
+        let fold = HtmlFold {
+            enable: true,
+            level: Some(1),
+        };
+        let html = sidebar(&summary, "thanks.md", &fold, false);
+        assert!(html.contains(&item("expanded", "Start", "guide/start.html")));
+        assert!(html.contains(&item("collapsed", "Install", "guide/install.html")));
+    }
+
+    #[tokio::test]
+    async fn sections_with_the_current_chapter_are_expanded() {
+        let (_dir, summary) = summary(SUMMARY).await;
+        let fold = HtmlFold {
+            enable: true,
+            level: None,
+        };
+        let html = sidebar(&summary, "guide/linux.md", &fold, false);
+
+        assert!(html.contains(r#"<li class="chapter-item expanded"><div class="chapter-link"><a href="../guide/start.html">Start</a>"#));
+        assert!(html.contains(r#"<li class="chapter-item expanded"><div class="chapter-link"><a href="../guide/install.html">Install</a>"#));
+        assert!(html.contains(r#"<li class="chapter-item"><div class="chapter-link"><a href="../guide/linux.html" class="active" aria-current="page">Linux</a>"#));
+    }
+
+    #[tokio::test]
+    async fn first_and_last_chapters_have_one_side() {
+        let (_dir, summary) = summary(SUMMARY).await;
+
+        assert_eq!(
+            previous_next(&summary, "intro.md"),
+            [None, page("Start", "guide/start.html")]
+        );
+        assert_eq!(
+            previous_next(&summary, "guide/install.md"),
+            [
+                page("Start", "../guide/start.html"),
+                page("Linux", "../guide/linux.html")
+            ]
+        );
+        // The draft, the external link and the second link to the start are
+        // not pages to navigate to
+        assert_eq!(
+            previous_next(&summary, "thanks.md"),
+            [page("Linux", "guide/linux.html"), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn pages_outside_the_summary_have_no_neighbours() {
+        let (_dir, summary) = summary(SUMMARY).await;
+
+        assert_eq!(previous_next(&summary, "unused.md"), [None, None]);
+        assert!(navigate(&summary, "unused.md", true, |nav| nav.breadcrumbs()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn previous_and_next_chapters_have_section_labels() {
+        let (_dir, summary) = summary(SUMMARY).await;
+        let (previous, next) = navigate(&summary, "guide/install.md", true, |nav| {
+            nav.previous_next()
+        });
+
+        assert_eq!(previous.unwrap().title, "1. Start");
+        assert_eq!(next.unwrap().title, "1.1.1. Linux");
+
+        let (_, next) = navigate(&summary, "intro.md", true, |nav| nav.previous_next());
+        assert_eq!(next.unwrap().title, "1. Start");
+    }
+
+    #[tokio::test]
+    async fn breadcrumbs_lead_to_the_current_chapter() {
+        let (_dir, summary) = summary(SUMMARY).await;
+        let breadcrumbs = navigate(&summary, "guide/linux.md", true, |nav| nav.breadcrumbs())
+            .into_iter()
+            .map(|crumb| (crumb.title, crumb.link))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            breadcrumbs,
+            [
+                ("Start".to_string(), Some("../guide/start.html".to_string())),
+                (
+                    "Install".to_string(),
+                    Some("../guide/install.html".to_string())
+                ),
+                ("Linux".to_string(), None),
+            ]
+        );
+    }
+}
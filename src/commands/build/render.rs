@@ -0,0 +1,226 @@
+use std::path::{Component, Path};
+
+use anyhow::Result;
+use markdown::mdast as ast;
+
+use crate::models::config_book::TextDirection;
+use crate::models::preprocessors_config::HtmlFold;
+use crate::models::Config;
+
+use super::navigation::{labelled_title, page_toc, Navigation};
+use super::theme::{BookContext, PageContext, Theme};
+use super::to_html::{Options, Warning};
+use super::{chapter_with_extension, read_chapter, to_html, Summary};
+
+/// Renders the chapters of a book into full HTML pages
+pub struct Renderer<'a> {
+    config: &'a Config,
+    src_dir: &'a Path,
+    theme: &'a Theme,
+    book: BookContext,
+    fold: HtmlFold,
+    /// Shows the numbers of the chapters in the sidebar and the titles
+    section_labels: bool,
+    options: Options,
+    /// Path where the book is published, used by pages that can be served at
+    /// any URL
+    site_path: String,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(config: &'a Config, src_dir: &'a Path, theme: &'a Theme) -> Self {
+        let html = config.html_config();
+        let playground = html.playground.clone().unwrap_or_default();
+        let code = html.code.clone().unwrap_or_default();
+
+        let book = BookContext {
+            default_theme: html.default_theme.unwrap_or_else(|| "light".to_string()),
+            preferred_dark_theme: html
+                .preferred_dark_theme
+                .unwrap_or_else(|| "navy".to_string()),
+            additional_css: html.additional_css.unwrap_or_default(),
+            additional_js: html.additional_js.unwrap_or_default(),
+        };
+
+        Self {
+            config,
+            src_dir,
+            theme,
+            book,
+            fold: html.fold.unwrap_or_default(),
+            section_labels: !html.no_section_label.unwrap_or_default(),
+            options: Options {
+                line_numbers: playground.line_numbers.unwrap_or_default(),
+                hidelines: code.hidelines.unwrap_or_default(),
+                edition: config.rust.edition,
+            },
+            site_path: config.site_path(),
+        }
+    }
+
+    /// Renders the chapter at `file` as the page at `output`, relative to the
+    /// build directory
+    pub async fn render_chapter(
+        &self,
+        summary: &Summary,
+        file: &Path,
+        output: &Path,
+    ) -> Result<String> {
+        let node = read_chapter(file).await?;
+
+        self.render_page(summary, file, node, output, None)
+    }
+
+    /// Renders the 404 page, whose links are resolved from the site path
+    /// because it is served for any missing URL
+    pub fn render_404(&self, summary: &Summary, file: &Path, node: ast::Node) -> Result<String> {
+        let output = Path::new("404.html");
+
+        self.render_page(summary, file, node, output, Some(&self.site_path))
+    }
+
+    /// Renders the markdown of a page, placed at `file` in the summary
+    fn render_page(
+        &self,
+        summary: &Summary,
+        file: &Path,
+        node: ast::Node,
+        output: &Path,
+        base_url: Option<&str>,
+    ) -> Result<String> {
+        let html = to_html(node, &self.options);
+
+        for warning in &html.warnings {
+            log::warn!("{}", describe_warning(file, warning));
+        }
+
+        let language = self.summary_language(summary);
+        let book_title = self
+            .config
+            .get_localized_title(language.as_deref())
+            .unwrap_or_default();
+        let description = self.config.get_localized_description(language.as_deref());
+
+        let chapter_title = summary
+            .find_chapter(self.src_dir, file)
+            .map(|chapter| labelled_title(chapter, self.section_labels));
+        let title = match chapter_title {
+            Some(chapter_title) if book_title.is_empty() => chapter_title,
+            Some(chapter_title) => format!("{chapter_title} - {book_title}"),
+            None => book_title.clone(),
+        };
+
+        let language = language
+            .or_else(|| self.config.book.language.clone())
+            .unwrap_or_else(|| "en".to_string());
+        let text_direction = self
+            .config
+            .book
+            .text_direction
+            .unwrap_or_else(|| TextDirection::from_lang_code(&language));
+
+        let path_to_root = path_to_root(output);
+        let navigation = Navigation {
+            src_dir: self.src_dir,
+            summary,
+            file,
+            path_to_root: &path_to_root,
+            section_labels: self.section_labels,
+        };
+        let sidebar = navigation.sidebar(&self.fold);
+        let (previous, next) = navigation.previous_next();
+        let breadcrumbs = navigation.breadcrumbs();
+
+        self.theme.render(&PageContext {
+            book: &self.book,
+            title,
+            book_title,
+            description,
+            language,
+            text_direction,
+            sidebar,
+            previous,
+            next,
+            breadcrumbs,
+            path_to_root,
+            base_url: base_url.map(str::to_string),
+            toc: page_toc(&html.headings),
+            content: html.content,
+        })
+    }
+
+    /// Localized books have a summary for each language in `src/<lang>`
+    fn summary_language(&self, summary: &Summary) -> Option<String> {
+        let relative_dir = summary.dir.strip_prefix(self.src_dir).ok()?;
+
+        let Some(Component::Normal(lang)) = relative_dir.components().next() else {
+            return None;
+        };
+        let lang = lang.to_str()?;
+
+        self.config
+            .language
+            .as_ref()?
+            .0
+            .contains_key(lang)
+            .then(|| lang.to_string())
+    }
+}
+
+/// Relative path from the page at `output` to the root of the build directory
+fn path_to_root(output: &Path) -> String {
+    let depth = output
+        .parent()
+        .map_or(0, |parent| parent.components().count());
+
+    "../".repeat(depth)
+}
+
+/// Message of a warning of a chapter, with the place of its file that causes it
+fn describe_warning(file: &Path, warning: &Warning) -> String {
+    // Links can omit the extension of the file
+    let file = ["md", "mdx"]
+        .into_iter()
+        .map(|extension| chapter_with_extension(file, extension))
+        .find(|path| path.is_file())
+        .unwrap_or_else(|| file.to_path_buf());
+
+    match &warning.position {
+        Some(position) => format!(
+            "{}\n  --> {}:{}:{}",
+            warning.message,
+            file.display(),
+            position.start.line,
+            position.start.column
+        ),
+        None => format!("{}\n  --> {}", warning.message, file.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use markdown::unist::{Point, Position};
+
+    use super::*;
+
+    #[test]
+    fn warnings_show_the_file_of_the_chapter() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("chapter-1.2.mdx");
+        std::fs::write(&file, "").unwrap();
+
+        let warning = Warning {
+            message: "Footnote [^a] is not defined".to_string(),
+            position: Some(Position {
+                start: Point::new(3, 5, 20),
+                end: Point::new(3, 9, 24),
+            }),
+        };
+
+        // Links can omit the extension
+        assert_eq!(
+            describe_warning(&dir.path().join("chapter-1.2"), &warning),
+            format!("Footnote [^a] is not defined\n  --> {}:3:5", file.display())
+        );
+    }
+}
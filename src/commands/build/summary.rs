@@ -1,12 +1,16 @@
+mod diagnostic;
+pub use diagnostic::{Diagnostic, Diagnostics, Severity, Span};
+
 use std::collections::HashSet;
+use std::fmt;
 use std::io;
-use std::path::PathBuf;
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
 
 use markdown::mdast as ast;
+use markdown::unist::Position;
 use tokio::fs;
 
-use crate::utils::SafeRemove;
+use crate::utils::NormalizePath;
 
 #[derive(Debug, thiserror::Error)]
 pub enum SummaryError {
@@ -16,16 +20,36 @@ pub enum SummaryError {
     IO(#[from] io::Error),
     #[error("Cannot open {0}: {1}")]
     HandledIo(PathBuf, io::Error),
-    #[error("Cannot parse summary: {0}")]
-    Parse(markdown::message::Message),
+    #[error("{0}")]
+    Diagnostics(Diagnostics),
     #[error("Summary has no root. Open an issue with all the context")]
     NoRoot,
 }
 
+/// Kind of an entry of the summary
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeNodeKind {
+    /// Chapter of the book, or a label when it has no link
+    Chapter,
+    /// Chapter that is not written yet, like `[Title]()`
+    Draft,
+    /// Title of a part of the numbered chapters, like `# Title`
+    PartTitle,
+    /// Line between groups of chapters, like `---`
+    Separator,
+}
+
+/// Number of a numbered chapter, like `1.2.`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SectionNumber(pub Vec<u32>);
+
 #[derive(Clone, Debug)]
 pub struct TreeNode {
+    pub kind: TreeNodeKind,
     pub title: String,
     pub href: Option<String>,
+    /// Number of the chapter, only for the numbered chapters
+    pub number: Option<SectionNumber>,
 
     pub children: Vec<TreeNode>,
 }
@@ -33,15 +57,91 @@ pub struct TreeNode {
 #[derive(Clone, Debug)]
 pub struct Summary {
     pub dir: PathBuf,
+    /// Numbered chapters, with the part titles and separators between them
     pub list: Vec<TreeNode>,
+    /// Prefix chapters, before the numbered ones
     pub root: Vec<TreeNode>,
+    /// Suffix chapters, after the numbered ones
+    pub suffix: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    fn new(kind: TreeNodeKind, title: impl Into<String>) -> Self {
+        Self {
+            kind,
+            title: title.into(),
+            href: None,
+            number: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Checks if this entry links to `file`, `summary_dir` being the directory
+    /// of the summary that contains it
+    pub fn links_to(&self, src_path: &Path, summary_dir: &Path, file: &Path) -> bool {
+        self.href
+            .as_deref()
+            .and_then(|href| resolve_link(src_path, summary_dir, href))
+            .is_some_and(|linked| same_chapter(&linked, file))
+    }
+
+    /// Checks if this entry or any of its descendants links to `file`
+    pub fn contains(&self, src_path: &Path, summary_dir: &Path, file: &Path) -> bool {
+        self.links_to(src_path, summary_dir, file)
+            || self
+                .children
+                .iter()
+                .any(|child| child.contains(src_path, summary_dir, file))
+    }
+}
+
+impl fmt::Display for SectionNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for number in &self.0 {
+            write!(f, "{number}.")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Summary {
+    /// Top level entries, in the order of the summary
+    pub fn entries(&self) -> impl Iterator<Item = &TreeNode> {
+        self.root.iter().chain(&self.list).chain(&self.suffix)
+    }
+
+    /// Finds the entry of the summary that links to `file`, in the order of
+    /// the summary
+    pub fn find_chapter(&self, src_path: &Path, file: &Path) -> Option<&TreeNode> {
+        fn find<'a>(
+            src_path: &Path,
+            dir: &Path,
+            nodes: impl IntoIterator<Item = &'a TreeNode>,
+            file: &Path,
+        ) -> Option<&'a TreeNode> {
+            nodes.into_iter().find_map(|node| {
+                if node.links_to(src_path, dir, file) {
+                    Some(node)
+                } else {
+                    find(src_path, dir, &node.children, file)
+                }
+            })
+        }
+
+        find(src_path, &self.dir, self.entries(), file)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct SummaryParser<'a> {
     pub all_files: HashSet<PathBuf>,
     pub src_path: &'a PathBuf,
+    /// Problems found in the parsed summaries
+    pub diagnostics: Vec<Diagnostic>,
     summary_dir: Option<PathBuf>,
+    /// Source of the summary being parsed
+    source: String,
 }
 
 impl<'a> SummaryParser<'a> {
@@ -49,13 +149,15 @@ impl<'a> SummaryParser<'a> {
         Self {
             src_path,
             all_files: HashSet::new(),
+            diagnostics: Vec::new(),
             summary_dir: None,
+            source: String::new(),
         }
     }
 }
 
 impl<'a> SummaryParser<'a> {
-    pub async fn parse_dir(&mut self, dir: &PathBuf) -> Result<Summary, SummaryError> {
+    pub async fn parse_dir(&mut self, dir: &Path) -> Result<Summary, SummaryError> {
         let sumary_path = dir.join("SUMMARY.md");
 
         let raw = match fs::read_to_string(&sumary_path).await {
@@ -66,46 +168,117 @@ impl<'a> SummaryParser<'a> {
             Err(err) => return Err(SummaryError::HandledIo(sumary_path, err)),
         };
 
-        let raw = markdown::to_mdast(&raw, &markdown::ParseOptions::mdx())
-            .map_err(|err| SummaryError::Parse(err))?;
+        let node = match markdown::to_mdast(&raw, &markdown::ParseOptions::mdx()) {
+            Ok(node) => node,
+            Err(message) => {
+                let span = message.place.map(|place| match *place {
+                    markdown::message::Place::Position(position) => Span::new(&raw, &position),
+                    markdown::message::Place::Point(point) => Span::new(
+                        &raw,
+                        &Position {
+                            start: point.clone(),
+                            end: point,
+                        },
+                    ),
+                });
+
+                return Err(SummaryError::Diagnostics(Diagnostics(vec![Diagnostic {
+                    severity: Severity::Error,
+                    code: "invalid-markdown",
+                    message: message.reason,
+                    file: sumary_path,
+                    span,
+                }])));
+            }
+        };
 
         self.summary_dir = Some(dir.to_path_buf());
-        let (root, list) = self.parse(raw)?;
-        let dir = self.summary_dir.take().unwrap();
+        self.source = raw;
+        let mut summary = self.parse(node)?;
+        summary.dir = self.summary_dir.take().unwrap();
 
-        Ok(Summary { dir, list, root })
+        Ok(summary)
     }
 
-    fn parse(&mut self, node: ast::Node) -> Result<(Vec<TreeNode>, Vec<TreeNode>), SummaryError> {
+    fn parse(&mut self, node: ast::Node) -> Result<Summary, SummaryError> {
         match node {
-            ast::Node::Root(root) => self.visit_root(root),
+            ast::Node::Root(root) => Ok(self.visit_root(root)),
             _ => Err(SummaryError::NoRoot),
         }
     }
 
-    fn visit_root(
-        &mut self,
-        root: ast::Root,
-    ) -> Result<(Vec<TreeNode>, Vec<TreeNode>), SummaryError> {
-        let mut root_list = vec![];
-        let mut list = vec![];
+    /// Visits the sections of the summary, as mdBook does: the prefix
+    /// chapters, the numbered chapters in lists, which can be split in parts
+    /// with headings, and the suffix chapters
+    fn visit_root(&mut self, root: ast::Root) -> Summary {
+        let mut summary = Summary {
+            dir: PathBuf::new(),
+            list: vec![],
+            root: vec![],
+            suffix: vec![],
+        };
+        let mut children = root.children.into_iter().peekable();
+
+        // The first heading is the title of the summary, not of a part
+        if let Some(ast::Node::Heading(_)) = children.peek() {
+            children.next();
+        }
+
+        for child in children {
+            // The numbered chapters start with the first list or part, and the
+            // suffix chapters with the first paragraph after them
+            let section = match (summary.list.is_empty(), summary.suffix.is_empty()) {
+                (true, _) => &mut summary.root,
+                (false, true) => &mut summary.list,
+                (false, false) => &mut summary.suffix,
+            };
 
-        for child in root.children {
             match child {
-                ast::Node::Paragraph(paragraph) => self
-                    .visit_paragraph(paragraph)
-                    .into_iter()
-                    .for_each(|child| root_list.push(child)),
+                ast::Node::Paragraph(paragraph) => {
+                    let chapters = self.visit_paragraph(paragraph);
+
+                    if summary.list.is_empty() {
+                        summary.root.extend(chapters);
+                    } else {
+                        summary.suffix.extend(chapters);
+                    }
+                }
+
+                ast::Node::List(list_node) => {
+                    if !summary.suffix.is_empty() {
+                        self.warn(
+                            "numbered-after-suffix",
+                            "Numbered chapters can not follow the suffix chapters",
+                            list_node.position.as_ref(),
+                        );
+                    }
+
+                    let chapters = self.visit_list(list_node);
+                    summary.list.extend(chapters);
+                }
+
+                ast::Node::Heading(heading) => {
+                    let title = ast::Node::Heading(heading).to_string();
+                    let part = TreeNode::new(TreeNodeKind::PartTitle, title);
 
-                ast::Node::List(list_node) => list = self.visit_list(list_node),
+                    summary.list.push(part);
+                }
 
-                e => {
-                    log::warn!("Unexpected node: {e:#?}");
+                ast::Node::ThematicBreak(_) => {
+                    section.push(TreeNode::new(TreeNodeKind::Separator, ""));
                 }
+
+                node => self.warn(
+                    "unexpected-node",
+                    "A summary can only contain links, lists, headings and separators",
+                    node.position(),
+                ),
             }
         }
 
-        Ok((root_list, list))
+        number_chapters(&mut summary.list, &[]);
+
+        summary
     }
 
     fn visit_paragraph(&mut self, paragraph: ast::Paragraph) -> Vec<TreeNode> {
@@ -119,65 +292,59 @@ impl<'a> SummaryParser<'a> {
     fn visit_paragraph_child(&mut self, node: ast::Node) -> Option<TreeNode> {
         match node {
             ast::Node::Text(ast::Text { value: title, .. }) if !title.trim().is_empty() => {
-                Some(TreeNode {
-                    title: title.clone(),
-                    href: None,
-
-                    children: Vec::new(),
-                })
+                Some(TreeNode::new(TreeNodeKind::Chapter, title))
             }
             ast::Node::Link(ast::Link {
                 url,
                 children,
                 position,
                 ..
-            }) => match children.get(0) {
-                Some(ast::Node::Text(ast::Text { value: title, .. })) => {
-                    // Link can be to external sites, so we check it before add
-                    // to files list
-                    if !url.starts_with("http") {
-                        let path = if url.starts_with("/") {
-                            self.src_path.join(&url[1..])
-                        } else {
-                            self.summary_dir().join(&url)
-                        };
-
-                        self.all_files.insert(path);
+            }) => {
+                // Like mdBook, the title is the text of the link without its
+                // formatting, like code or emphasis
+                let title = children.iter().map(ToString::to_string).collect::<String>();
+
+                match title.trim().is_empty() {
+                    true => {
+                        self.warn(
+                            "link-without-text",
+                            "The link has no text to use as title of the chapter",
+                            position.as_ref(),
+                        );
+
+                        None
                     }
-
-                    Some(TreeNode {
-                        title: title.clone(),
-                        href: Some(url.clone()),
-
-                        children: Vec::new(),
-                    })
-                }
-                Some(_) => {
-                    if let Some(position) = position {
-                        let line = position.start.line;
-                        let column = position.start.column;
-
-                        log::warn!("Link (at {line}:{column}) has no text child");
-                    } else {
-                        log::warn!("Link (at <unknown>) has no text child");
+                    // Draft chapters link to nothing
+                    false if url.is_empty() => Some(TreeNode::new(TreeNodeKind::Draft, title)),
+                    false => {
+                        // Link can be to external sites, so we check it before add
+                        // to files list
+                        if let Some(path) = resolve_link(self.src_path, self.summary_dir(), &url) {
+                            self.all_files.insert(path);
+                        }
+
+                        Some(TreeNode {
+                            kind: TreeNodeKind::Chapter,
+                            title,
+                            href: Some(url),
+                            number: None,
+
+                            children: Vec::new(),
+                        })
                     }
-
-                    None
-                }
-                None => {
-                    if let Some(position) = position {
-                        let line = position.start.line;
-                        let column = position.start.column;
-
-                        log::warn!("Link (at {line}:{column}) has no children");
-                    } else {
-                        log::warn!("Link (at <unknown>) has no children");
-                    }
-
-                    None
                 }
-            },
-            _ => None,
+            }
+            // Whitespace between the links
+            ast::Node::Text(_) => None,
+            node => {
+                self.warn(
+                    "unexpected-node",
+                    "Only links and texts can be chapters",
+                    node.position(),
+                );
+
+                None
+            }
         }
     }
 
@@ -188,48 +355,428 @@ impl<'a> SummaryParser<'a> {
                 if let ast::Node::ListItem(list_item) = node {
                     self.visit_list_item(list_item)
                 } else {
-                    log::warn!("A list must have ListItem as children");
+                    self.warn(
+                        "invalid-list",
+                        "A list can only contain items",
+                        node.position(),
+                    );
                     None
                 }
             })
             .collect()
     }
 
-    fn visit_list_item(&mut self, mut list_item: ast::ListItem) -> Option<TreeNode> {
-        // Get the second child first due to `swap_remove` behaviour
-        // that keeps O(1) but break the order of the array
-        let second_child = list_item.children.safe_remove(1);
-        let first_child = list_item.children.safe_remove(0);
-
-        let mut root = if let Some(ast::Node::Paragraph(mut paragraph)) = first_child {
-            paragraph
-                .children
-                .safe_remove(0)
-                .map(|first_child| self.visit_paragraph_child(first_child))
-                .flatten()?
-        } else {
-            log::warn!("A list item should have a Link or Text as first child");
+    /// Visits an item of a list: a paragraph with its chapter, and the lists
+    /// of its sub-chapters. Anything else is reported, not dropped silently
+    fn visit_list_item(&mut self, list_item: ast::ListItem) -> Option<TreeNode> {
+        let mut children = list_item.children.into_iter();
+
+        let Some(ast::Node::Paragraph(paragraph)) = children.next() else {
+            self.warn(
+                "invalid-list-item",
+                "A list item must start with a link or a text",
+                list_item.position.as_ref(),
+            );
             return None;
         };
 
-        root.children = second_child.map_or_else(
-            || vec![],
-            |child| {
-                if let ast::Node::List(list) = child {
-                    self.visit_list(list)
-                } else {
-                    log::warn!("A list item may have a List as second child");
-                    vec![]
+        let mut inlines = paragraph.children.into_iter();
+        let mut root = inlines
+            .next()
+            .and_then(|first_child| self.visit_paragraph_child(first_child));
+
+        for node in inlines {
+            match node {
+                // Whitespace after the chapter
+                ast::Node::Text(text) if text.value.trim().is_empty() => {}
+                node => self.warn(
+                    "invalid-list-item",
+                    "A list item can only have one chapter, the rest is skipped",
+                    node.position(),
+                ),
+            }
+        }
+
+        for child in children {
+            match (child, &mut root) {
+                (ast::Node::List(list), Some(root)) => {
+                    let chapters = self.visit_list(list);
+                    root.children.extend(chapters);
                 }
-            },
-        );
+                (ast::Node::List(list), None) => self.warn(
+                    "invalid-list-item",
+                    "The sub-chapters of an item without a chapter are skipped",
+                    list.position.as_ref(),
+                ),
+                (child, _) => self.warn(
+                    "invalid-list-item",
+                    "A list item can only contain a list after its title",
+                    child.position(),
+                ),
+            }
+        }
+
+        root
+    }
 
-        Some(root)
+    /// Reports a problem of the summary being parsed
+    fn warn(&mut self, code: &'static str, message: &str, position: Option<&Position>) {
+        self.diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            code,
+            message: message.to_string(),
+            file: self.summary_dir().join("SUMMARY.md"),
+            span: position.map(|position| Span::new(&self.source, position)),
+        });
     }
 
-    fn summary_dir(&mut self) -> &PathBuf {
+    fn summary_dir(&self) -> &PathBuf {
         self.summary_dir
             .as_ref()
             .expect("Summary dir is setted before parse")
     }
 }
+
+/// Numbers the chapters of the lists by their nesting. Parts do not restart
+/// the numbers
+fn number_chapters(nodes: &mut [TreeNode], parent: &[u32]) {
+    let chapters = nodes
+        .iter_mut()
+        .filter(|node| matches!(node.kind, TreeNodeKind::Chapter | TreeNodeKind::Draft));
+
+    for (chapter, index) in chapters.zip(1..) {
+        let mut number = parent.to_vec();
+        number.push(index);
+
+        number_chapters(&mut chapter.children, &number);
+        chapter.number = Some(SectionNumber(number));
+    }
+}
+
+/// Resolves a link of a summary to the file it points to.
+///
+/// Absolute links (`/chapter.md`) are relative to the source directory and the
+/// others are relative to the directory of the summary. External links return `None`
+pub fn resolve_link(src_path: &Path, summary_dir: &Path, url: &str) -> Option<PathBuf> {
+    if url.starts_with("http") {
+        return None;
+    }
+
+    let path = match url.strip_prefix('/') {
+        Some(url) => src_path.join(url),
+        None => summary_dir.join(url),
+    };
+
+    Some(path.normalize())
+}
+
+/// Chapters can be linked with or without the markdown extension
+pub fn same_chapter(a: &Path, b: &Path) -> bool {
+    chapter_base(a) == chapter_base(b)
+}
+
+/// Path of a chapter without its markdown extension. Other extensions are part
+/// of the name, like in `chapter-1.2`
+pub fn chapter_base(path: &Path) -> PathBuf {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("md" | "mdx") => path.with_extension(""),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Path of a chapter with `extension` instead of its markdown extension
+pub fn chapter_with_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut path = chapter_base(path).into_os_string();
+    path.push(".");
+    path.push(extension);
+
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn parse(summary: &str) -> (Summary, HashSet<PathBuf>) {
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().to_path_buf();
+        std::fs::write(src_path.join("SUMMARY.md"), summary).unwrap();
+
+        let mut parser = SummaryParser::new(&src_path);
+        let summary = parser.parse_dir(&src_path).await.unwrap();
+
+        let files = parser
+            .all_files
+            .iter()
+            .map(|file| file.strip_prefix(&src_path).unwrap().to_path_buf())
+            .collect();
+
+        (summary, files)
+    }
+
+    /// Numbers and titles of the entries, indented by depth
+    fn numbers(nodes: &[TreeNode]) -> Vec<String> {
+        fn visit(nodes: &[TreeNode], depth: usize, numbers: &mut Vec<String>) {
+            for node in nodes {
+                let indent = "  ".repeat(depth);
+                let number = node.number.as_ref().map(ToString::to_string);
+                numbers.push(format!(
+                    "{indent}{} {}",
+                    number.unwrap_or_default(),
+                    node.title
+                ));
+                visit(&node.children, depth + 1, numbers);
+            }
+        }
+
+        let mut numbers = Vec::new();
+        visit(nodes, 0, &mut numbers);
+        numbers
+    }
+
+    /// Kinds and titles of the entries, indented by depth
+    fn outline(nodes: &[TreeNode]) -> Vec<String> {
+        fn visit(nodes: &[TreeNode], depth: usize, outline: &mut Vec<String>) {
+            for node in nodes {
+                let indent = "  ".repeat(depth);
+                outline.push(format!("{indent}{:?} {}", node.kind, node.title));
+                visit(&node.children, depth + 1, outline);
+            }
+        }
+
+        let mut outline = Vec::new();
+        visit(nodes, 0, &mut outline);
+        outline
+    }
+
+    #[tokio::test]
+    async fn mdbook_grammar() {
+        let (summary, files) = parse(
+            "# Summary
+
+[Introduction](intro.md)
+
+---
+
+# Guide
+
+- [Install](install.md)
+    - [Draft]()
+
+---
+
+# Reference
+
+- [Config](config.md)
+
+---
+
+[Contributors](contributors.md)
+",
+        )
+        .await;
+
+        assert_eq!(
+            outline(&summary.root),
+            ["Chapter Introduction", "Separator "]
+        );
+        assert_eq!(
+            outline(&summary.list),
+            [
+                "PartTitle Guide",
+                "Chapter Install",
+                "  Draft Draft",
+                "Separator ",
+                "PartTitle Reference",
+                "Chapter Config",
+                "Separator ",
+            ]
+        );
+        assert_eq!(outline(&summary.suffix), ["Chapter Contributors"]);
+
+        let mut files = files.into_iter().collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(
+            files,
+            ["config.md", "contributors.md", "install.md", "intro.md"].map(PathBuf::from)
+        );
+    }
+
+    #[tokio::test]
+    async fn numbered_chapters() {
+        let (summary, _) = parse(
+            "[Intro](intro.md)
+
+# Part
+
+- [One](one.md)
+    - [One A](one-a.md)
+    - [Draft]()
+        - [Deep](deep.md)
+
+---
+
+# Other part
+
+- [Two](two.md)
+
+[Suffix](suffix.md)
+",
+        )
+        .await;
+
+        assert_eq!(numbers(&summary.root), [" Intro"]);
+        assert_eq!(
+            numbers(&summary.list),
+            [
+                " Part",
+                "1. One",
+                "  1.1. One A",
+                "  1.2. Draft",
+                "    1.2.1. Deep",
+                " ",
+                " Other part",
+                "2. Two",
+            ]
+        );
+        assert_eq!(numbers(&summary.suffix), [" Suffix"]);
+    }
+
+    #[tokio::test]
+    async fn formatted_link_titles() {
+        let (summary, files) = parse(
+            "- [`Error`](error.md)
+- [**Bold** and *emphasis*](bold.md)
+    - [The `?` operator]()
+",
+        )
+        .await;
+
+        assert_eq!(
+            outline(&summary.list),
+            [
+                "Chapter Error",
+                "Chapter Bold and emphasis",
+                "  Draft The ? operator",
+            ]
+        );
+        assert_eq!(
+            files,
+            HashSet::from([PathBuf::from("error.md"), PathBuf::from("bold.md")])
+        );
+    }
+
+    #[tokio::test]
+    async fn lists_are_not_replaced() {
+        let (summary, _) = parse("- [One](one.md)\n\n---\n\n- [Two](two.md)\n").await;
+
+        assert_eq!(
+            outline(&summary.list),
+            ["Chapter One", "Separator ", "Chapter Two"]
+        );
+        assert!(summary.root.is_empty());
+    }
+
+    #[tokio::test]
+    async fn summary_without_title() {
+        let (summary, _) = parse("[Intro](intro.md)\n\n- Section\n    - [Page](page.md)\n").await;
+
+        assert_eq!(outline(&summary.root), ["Chapter Intro"]);
+        assert_eq!(
+            outline(&summary.list),
+            ["Chapter Section", "  Chapter Page"]
+        );
+    }
+
+    async fn diagnostics(summary: &str) -> Vec<Diagnostic> {
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().to_path_buf();
+        std::fs::write(src_path.join("SUMMARY.md"), summary).unwrap();
+
+        let mut parser = SummaryParser::new(&src_path);
+        parser.parse_dir(&src_path).await.unwrap();
+
+        assert!(parser
+            .diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.file == src_path.join("SUMMARY.md")));
+
+        parser.diagnostics
+    }
+
+    #[tokio::test]
+    async fn problems_are_reported_with_their_place() {
+        let diagnostics = diagnostics("- [Intro](intro.md)\n- [](empty.md)\n- *Emphasis*\n").await;
+        let places = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let span = diagnostic.span.as_ref().unwrap();
+                (diagnostic.code, span.line, span.column, span.end_column)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            places,
+            [
+                ("link-without-text", 2, 3, Some(15)),
+                ("unexpected-node", 3, 3, Some(13)),
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == Severity::Warning));
+    }
+
+    /// Codes and lines of the diagnostics of a summary
+    async fn problems(summary: &str) -> Vec<(&'static str, usize)> {
+        diagnostics(summary)
+            .await
+            .iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.span.as_ref().unwrap().line))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn extra_content_of_items_is_reported() {
+        let summary = "- [C](c.md)
+
+  text
+
+  - [D](d.md)
+- [A](a.md) [B](b.md)
+";
+        let (parsed, _) = parse(summary).await;
+
+        assert_eq!(
+            outline(&parsed.list),
+            ["Chapter C", "  Chapter D", "Chapter A"]
+        );
+        assert_eq!(
+            problems(summary).await,
+            [("invalid-list-item", 3), ("invalid-list-item", 6)]
+        );
+    }
+
+    #[tokio::test]
+    async fn sub_chapters_of_invalid_items_are_reported() {
+        assert_eq!(
+            problems("- [](empty.md)\n    - [Child](child.md)\n").await,
+            [("link-without-text", 1), ("invalid-list-item", 2)]
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_markdown_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let src_path = dir.path().to_path_buf();
+        std::fs::write(src_path.join("SUMMARY.md"), "- [Intro](intro.md)\n<div\n").unwrap();
+
+        let mut parser = SummaryParser::new(&src_path);
+        let Err(SummaryError::Diagnostics(diagnostics)) = parser.parse_dir(&src_path).await else {
+            panic!("The summary should be invalid");
+        };
+
+        assert_eq!(diagnostics.errors().count(), 1);
+        assert_eq!(diagnostics.0[0].span.as_ref().unwrap().line, 3);
+    }
+}
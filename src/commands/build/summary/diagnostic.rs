@@ -0,0 +1,159 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use markdown::unist::Position;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Problem found in a summary, with the place of the source that causes it
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Short name of the kind of problem, like `link-without-text`
+    pub code: &'static str,
+    pub message: String,
+    pub file: PathBuf,
+    pub span: Option<Span>,
+}
+
+/// Place of a diagnostic in its file, starting from 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub column: usize,
+    /// Column after the end of the span, if it ends in the same line
+    pub end_column: Option<usize>,
+    /// Line of the source that contains the start of the span
+    pub source: String,
+}
+
+impl Span {
+    /// Span of a node of the markdown tree of `source`
+    pub fn new(source: &str, position: &Position) -> Self {
+        let line = position.start.line;
+        let end_column = (position.end.line == line).then_some(position.end.column);
+
+        Self {
+            line,
+            column: position.start.column,
+            end_column,
+            source: source.lines().nth(line - 1).unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// Every problem found in the summaries of a book
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Renders the diagnostic like rustc does, with a caret under the source
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}[{}]: {}", self.severity, self.code, self.message)?;
+
+        let Some(span) = &self.span else {
+            return write!(f, "  --> {}", self.file.display());
+        };
+
+        let gutter = " ".repeat(span.line.to_string().len());
+
+        // Columns count characters, so the carets are aligned with them
+        let start = span.column.max(1) - 1;
+        let length = span
+            .end_column
+            .map_or(span.source.chars().count().saturating_sub(start), |end| {
+                end.saturating_sub(span.column)
+            })
+            .max(1);
+        let indent = span
+            .source
+            .chars()
+            .take(start)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+
+        writeln!(
+            f,
+            "{gutter}--> {}:{}:{}",
+            self.file.display(),
+            span.line,
+            span.column
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", span.line, span.source)?;
+        write!(f, "{gutter} | {indent}{}", "^".repeat(length))
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for diagnostic in &self.0 {
+            writeln!(f, "{diagnostic}\n")?;
+        }
+
+        let errors = self.errors().count();
+        let warnings = self.0.len() - errors;
+
+        write!(
+            f,
+            "The summaries have {errors} error(s) and {warnings} warning(s)"
+        )
+    }
+}
+
+impl Diagnostics {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.0
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use markdown::unist::Point;
+
+    use super::*;
+
+    #[test]
+    fn caret_is_under_the_span() {
+        let source = "# Summary\n\n- [Intro](intro.md)\n";
+        let position = Position {
+            start: Point::new(3, 3, 13),
+            end: Point::new(3, 20, 30),
+        };
+        let diagnostic = Diagnostic {
+            severity: Severity::Warning,
+            code: "test",
+            message: "Problem".to_string(),
+            file: PathBuf::from("src/SUMMARY.md"),
+            span: Some(Span::new(source, &position)),
+        };
+
+        assert_eq!(
+            diagnostic.to_string(),
+            "warning[test]: Problem
+ --> src/SUMMARY.md:3:3
+  |
+3 | - [Intro](intro.md)
+  |   ^^^^^^^^^^^^^^^^^"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use std::io;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use handlebars::Handlebars;
+use serde::Serialize;
+use tokio::fs;
+
+use crate::models::config_book::TextDirection;
+
+use super::navigation::{NavLink, TocEntry};
+use super::to_html::theme_css;
+use super::write_file;
+
+const INDEX: &str = include_str!("theme/index.hbs");
+const BOOK_CSS: &[u8] = include_bytes!("theme/book.css");
+const BOOK_JS: &[u8] = include_bytes!("theme/book.js");
+
+/// Layout and static files used to render the pages of a book.
+///
+/// Files found in the theme directory replace the builtin ones, so a theme only
+/// needs to contain the files it wants to override
+pub struct Theme {
+    templates: Handlebars<'static>,
+    assets: Vec<(&'static str, Vec<u8>)>,
+}
+
+/// Variables shared by every page of the book
+#[derive(Debug, Serialize)]
+pub struct BookContext {
+    pub default_theme: String,
+    pub preferred_dark_theme: String,
+    pub additional_css: Vec<String>,
+    pub additional_js: Vec<String>,
+}
+
+/// Variables available to the `index.hbs` template
+#[derive(Debug, Serialize)]
+pub struct PageContext<'a> {
+    #[serde(flatten)]
+    pub book: &'a BookContext,
+    pub title: String,
+    pub book_title: String,
+    pub description: Option<String>,
+    pub language: String,
+    pub text_direction: TextDirection,
+    pub path_to_root: String,
+    /// URL that relative links are resolved from, instead of the page URL
+    pub base_url: Option<String>,
+    pub sidebar: String,
+    pub previous: Option<NavLink>,
+    pub next: Option<NavLink>,
+    pub breadcrumbs: Vec<NavLink>,
+    /// Headings of the chapter, for the "On this page" panel
+    pub toc: Vec<TocEntry>,
+    pub content: String,
+}
+
+impl Theme {
+    pub async fn load(theme_dir: &Path) -> Result<Self> {
+        let index = match read_override(theme_dir, "index.hbs").await? {
+            Some(index) => String::from_utf8(index)
+                .map_err(|err| anyhow!("Cannot read {theme_dir:?}/index.hbs.\n  Cause: {err}"))?,
+            None => INDEX.to_string(),
+        };
+
+        let mut templates = Handlebars::new();
+        templates
+            .register_template_string("index", index)
+            .map_err(|err| anyhow!("Cannot parse the page template.\n  Cause: {err}"))?;
+
+        let mut assets = Vec::new();
+
+        for (name, default) in [("book.css", BOOK_CSS), ("book.js", BOOK_JS)] {
+            let content = read_override(theme_dir, name)
+                .await?
+                .unwrap_or_else(|| default.to_vec());
+
+            assets.push((name, content));
+        }
+
+        let highlight_css = match read_override(theme_dir, "highlight.css").await? {
+            Some(content) => content,
+            None => theme_css().into_bytes(),
+        };
+        assets.push(("highlight.css", highlight_css));
+
+        Ok(Self { templates, assets })
+    }
+
+    pub fn render(&self, page: &PageContext) -> Result<String> {
+        self.templates
+            .render("index", page)
+            .map_err(|err| anyhow!("Cannot render the page.\n  Cause: {err}"))
+    }
+
+    /// Writes the static files of the theme to the root of the build directory
+    pub async fn write_assets(&self, build_dir: &Path) -> Result<()> {
+        for (name, content) in &self.assets {
+            write_file(&build_dir.join(name), content).await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn read_override(theme_dir: &Path, name: &str) -> Result<Option<Vec<u8>>> {
+    let path = theme_dir.join(name);
+
+    match fs::read(&path).await {
+        Ok(content) => {
+            log::info!("Using {name} from the theme directory");
+            Ok(Some(content))
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(anyhow!("Cannot read {path:?}.\n  Cause: {err}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(book: &BookContext) -> PageContext<'_> {
+        PageContext {
+            book,
+            title: "Chapter - Book".to_string(),
+            book_title: "Book".to_string(),
+            description: None,
+            language: "en".to_string(),
+            text_direction: TextDirection::LeftToRight,
+            path_to_root: String::new(),
+            base_url: None,
+            sidebar: String::new(),
+            previous: None,
+            next: None,
+            breadcrumbs: Vec::new(),
+            toc: Vec::new(),
+            content: "<p>Content</p>".to_string(),
+        }
+    }
+
+    fn book() -> BookContext {
+        BookContext {
+            default_theme: "light".to_string(),
+            preferred_dark_theme: "navy".to_string(),
+            additional_css: Vec::new(),
+            additional_js: Vec::new(),
+        }
+    }
+
+    /// Loads the theme at `theme_dir` and writes its assets to `build_dir`
+    async fn load(theme_dir: &Path, build_dir: &Path) -> Theme {
+        let theme = Theme::load(theme_dir).await.unwrap();
+        theme.write_assets(build_dir).await.unwrap();
+
+        theme
+    }
+
+    #[tokio::test]
+    async fn theme_files_replace_the_builtin_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let (theme_dir, build_dir) = (dir.path().join("theme"), dir.path().join("book"));
+
+        std::fs::create_dir_all(&theme_dir).unwrap();
+        std::fs::write(
+            theme_dir.join("index.hbs"),
+            "<main lang=\"{{language}}\">{{{content}}}</main>",
+        )
+        .unwrap();
+        std::fs::write(theme_dir.join("book.css"), "body { color: red; }").unwrap();
+
+        let theme = load(&theme_dir, &build_dir).await;
+
+        assert_eq!(
+            theme.render(&page(&book())).unwrap(),
+            "<main lang=\"en\"><p>Content</p></main>"
+        );
+        assert_eq!(
+            std::fs::read(build_dir.join("book.css")).unwrap(),
+            b"body { color: red; }"
+        );
+        // The files that are not overridden are the builtin ones
+        assert_eq!(std::fs::read(build_dir.join("book.js")).unwrap(), BOOK_JS);
+        assert_eq!(
+            std::fs::read_to_string(build_dir.join("highlight.css")).unwrap(),
+            theme_css()
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_theme_uses_the_builtin_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let build_dir = dir.path().join("book");
+
+        let theme = load(&dir.path().join("missing"), &build_dir).await;
+        let html = theme.render(&page(&book())).unwrap();
+
+        assert!(html.contains("<title>Chapter - Book</title>"));
+        assert!(html.contains("<p>Content</p>"));
+        assert_eq!(std::fs::read(build_dir.join("book.css")).unwrap(), BOOK_CSS);
+        assert_eq!(std::fs::read(build_dir.join("book.js")).unwrap(), BOOK_JS);
+    }
+
+    #[tokio::test]
+    async fn invalid_template_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("index.hbs"), "{{#if}}").unwrap();
+
+        assert!(Theme::load(dir.path()).await.is_err());
+    }
+}
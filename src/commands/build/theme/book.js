@@ -0,0 +1,119 @@
+"use strict";
+
+(function themes() {
+    var html = document.documentElement;
+    var themeList = document.getElementById("theme-list");
+    var themes = Array.prototype.map.call(themeList.options, function (option) {
+        return option.value;
+    });
+
+    function currentTheme() {
+        return themes.find(function (theme) {
+            return html.classList.contains(theme);
+        }) || html.dataset.defaultTheme;
+    }
+
+    function setTheme(theme) {
+        themes.forEach(function (theme) {
+            html.classList.remove(theme);
+        });
+        html.classList.add(theme);
+
+        try { localStorage.setItem("mdbook-theme", theme); } catch (e) { }
+    }
+
+    themeList.value = currentTheme();
+    themeList.addEventListener("change", function () {
+        setTheme(themeList.value);
+    });
+})();
+
+(function sidebar() {
+    var body = document.body;
+    var toggle = document.getElementById("sidebar-toggle");
+
+    try {
+        if (localStorage.getItem("mdbook-sidebar") === "hidden") {
+            body.classList.add("sidebar-hidden");
+        }
+    } catch (e) { }
+
+    toggle.addEventListener("click", function () {
+        var hidden = body.classList.toggle("sidebar-hidden");
+
+        try { localStorage.setItem("mdbook-sidebar", hidden ? "hidden" : "visible"); } catch (e) { }
+    });
+})();
+
+(function tableOfContents() {
+    var toggles = document.querySelectorAll("#sidebar .toggle");
+
+    Array.prototype.forEach.call(toggles, function (toggle) {
+        toggle.addEventListener("click", function () {
+            var item = toggle.closest(".chapter-item");
+
+            item.classList.toggle("expanded");
+            item.classList.toggle("collapsed");
+        });
+    });
+
+    var active = document.querySelector("#sidebar a.active");
+    if (active) {
+        active.scrollIntoView({ block: "center" });
+    }
+})();
+
+(function chapterNavigation() {
+    document.addEventListener("keydown", function (event) {
+        if (event.altKey || event.ctrlKey || event.metaKey || event.shiftKey) {
+            return;
+        }
+        if (event.target.closest("input, textarea, select, [contenteditable]")) {
+            return;
+        }
+
+        var rtl = document.documentElement.dir === "rtl";
+        var rel = null;
+
+        if (event.key === "ArrowLeft") {
+            rel = rtl ? "next" : "prev";
+        } else if (event.key === "ArrowRight") {
+            rel = rtl ? "prev" : "next";
+        }
+
+        var link = rel && document.querySelector('.nav-chapters a[rel="' + rel + '"]');
+        if (link) {
+            event.preventDefault();
+            window.location.href = link.href;
+        }
+    });
+})();
+
+(function hiddenLines() {
+    var blocks = document.querySelectorAll("pre");
+
+    Array.prototype.forEach.call(blocks, function (pre) {
+        if (!pre.querySelector(".boring")) {
+            return;
+        }
+
+        var button = document.createElement("button");
+        button.type = "button";
+        button.className = "code-button boring-toggle";
+        button.textContent = "⋯";
+
+        function update(shown) {
+            var label = shown ? "Hide hidden lines" : "Show hidden lines";
+            button.title = label;
+            button.setAttribute("aria-label", label);
+            button.setAttribute("aria-pressed", shown ? "true" : "false");
+        }
+
+        update(false);
+        button.addEventListener("click", function () {
+            update(pre.classList.toggle("show-boring"));
+        });
+
+        pre.appendChild(button);
+    });
+})();
@@ -1,17 +1,88 @@
+mod code;
+pub use code::{split_hidden_lines, CodeInfo};
+mod highlight;
+pub use highlight::theme_css;
 mod utils;
-use dashmap::mapref::one::RefMut;
-use utils::ToHtmlBuffer;
+pub use utils::{escape_attr, escape_text, Heading, Warning};
+use utils::{Definition, ToHtmlBuffer};
 
+use std::collections::HashMap;
 use std::fmt::Write;
 
 use markdown::mdast as ast;
 
-pub fn to_html(node: ast::Node) -> String {
-    let mut buffer = ToHtmlBuffer::new(true);
+use crate::models::rust_config::RustEdition;
 
+/// HTML of a chapter, with the headings needed to build its table of contents
+pub struct Html {
+    pub content: String,
+    pub headings: Vec<Heading>,
+    pub warnings: Vec<Warning>,
+}
+
+/// Settings of the book that change how the chapters are rendered
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Numbers the lines of the code blocks
+    pub line_numbers: bool,
+    /// Prefix of the hidden lines of the code blocks, by language
+    pub hidelines: HashMap<String, String>,
+    /// Edition of the Rust code blocks that do not set one
+    pub edition: Option<RustEdition>,
+}
+
+pub fn to_html(node: ast::Node, options: &Options) -> Html {
+    render(node, options, true)
+}
+
+fn render(node: ast::Node, options: &Options, beautify: bool) -> Html {
+    let mut buffer = ToHtmlBuffer::new(options.clone(), beautify);
+
+    collect_definitions(&mut buffer, &node);
     node_to_html(&mut buffer, node);
+    footnotes_to_html(&mut buffer);
+
+    buffer.warnings.sort_by_key(|warning| {
+        warning
+            .position
+            .as_ref()
+            .map(|position| position.start.offset)
+    });
 
-    buffer.buffer
+    Html {
+        content: buffer.buffer,
+        headings: buffer.headings,
+        warnings: buffer.warnings,
+    }
+}
+
+/// References can point to definitions placed anywhere in the document, so
+/// they are collected before rendering
+fn collect_definitions(buffer: &mut ToHtmlBuffer, node: &ast::Node) {
+    // The first definition wins when an identifier is duplicated
+    match node {
+        ast::Node::Definition(definition) => {
+            buffer
+                .definitions
+                .entry(definition.identifier.clone())
+                .or_insert_with(|| Definition {
+                    url: definition.url.clone(),
+                    title: definition.title.clone(),
+                });
+        }
+        ast::Node::FootnoteDefinition(definition) => {
+            buffer
+                .footnotes
+                .definitions
+                .entry(definition.identifier.clone())
+                .or_insert_with(|| definition.clone());
+        }
+        _ => {}
+    }
+
+    for child in node.children().into_iter().flatten() {
+        collect_definitions(buffer, child);
+    }
 }
 
 fn node_to_html(buffer: &mut ToHtmlBuffer, node: ast::Node) {
@@ -58,150 +129,526 @@ fn children_to_html(buffer: &mut ToHtmlBuffer, children: Vec<ast::Node>) {
 }
 
 fn blockquote_to_html(buffer: &mut ToHtmlBuffer, node: ast::Blockquote) {
-    let tag = buffer.tag("blockquote", "");
+    let tag = buffer.tag("blockquote", &[]);
     children_to_html(tag.buffer, node.children);
 }
 
-fn break_to_html(_buffer: &mut ToHtmlBuffer, node: ast::Break) {
-    todo!("{node:#?}")
+fn break_to_html(buffer: &mut ToHtmlBuffer, _node: ast::Break) {
+    buffer.void_tag("br", &[]);
+    buffer.push_newline();
 }
 
 fn code_to_html(buffer: &mut ToHtmlBuffer, node: ast::Code) {
-    let attrs = format!(r#"class="language-{}""#, node.lang.unwrap_or(String::new()));
-    let tag = buffer.tag("code", &attrs);
-    _ = tag.buffer.write_str(&node.value);
+    let info = CodeInfo::parse(node.lang.as_deref(), node.meta.as_deref());
+    let language = info.language.as_deref();
+
+    let prefix = info.hidelines().or_else(|| {
+        language
+            .and_then(|language| buffer.options.hidelines.get(language))
+            .map(String::as_str)
+    });
+    let code_lines = split_hidden_lines(&node.value, language, prefix);
+
+    // Hidden lines are highlighted too, so they are part of the code when
+    // shown and the highlighting of the visible ones is not broken
+    let code = code_lines
+        .iter()
+        .map(|line| line.text.as_ref())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let lines = highlight::highlight(&code, language);
+
+    // Playgrounds read the edition from the classes of the code
+    let edition = match buffer.options.edition {
+        Some(edition) if language == Some("rust") && info.edition().is_none() => {
+            Some(format!("edition{}", edition.as_str()))
+        }
+        _ => None,
+    };
+
+    let line_numbers = buffer.options.line_numbers;
+    let pre_class = if line_numbers {
+        "hl-code line-numbers"
+    } else {
+        "hl-code"
+    };
+
+    // The whitespace inside `<pre>` is visible, so nothing is beautified
+    let pre = buffer.inline_tag("pre", &[("class", pre_class)]);
+
+    let class = info
+        .language
+        .iter()
+        .map(|language| format!("language-{language}"))
+        .chain(info.attributes.iter().cloned())
+        .chain(edition)
+        .collect::<Vec<_>>()
+        .join(" ");
+    let attrs = if class.is_empty() {
+        vec![]
+    } else {
+        vec![("class", class.as_str())]
+    };
+    let code = pre.buffer.inline_tag("code", &attrs);
+
+    for (index, (line, code_line)) in lines.iter().zip(&code_lines).enumerate() {
+        let number = index + 1;
+        let line = CodeLineHtml {
+            html: line,
+            number,
+            highlighted: info.highlighted_lines.contains(&number),
+            line_ending: number < lines.len(),
+        };
+
+        // The line ending of a hidden line is hidden with it
+        if code_line.hidden {
+            let boring = code.buffer.inline_tag("span", &[("class", "boring")]);
+            line_to_html(boring.buffer, line, line_numbers);
+        } else {
+            line_to_html(code.buffer, line, line_numbers);
+        }
+    }
 }
 
-fn definition_to_html(_buffer: &mut ToHtmlBuffer, node: ast::Definition) {
-    todo!("{node:#?}")
+/// Highlighted line of a code block
+struct CodeLineHtml<'a> {
+    html: &'a str,
+    number: usize,
+    highlighted: bool,
+    line_ending: bool,
+}
+
+fn line_to_html(buffer: &mut ToHtmlBuffer, line: CodeLineHtml, line_numbers: bool) {
+    if line_numbers || line.highlighted {
+        line_with_wrapper_to_html(buffer, &line, line_numbers);
+    } else {
+        buffer.buffer.push_str(line.html);
+    }
+
+    if line.line_ending {
+        buffer.buffer.push('\n');
+    }
+}
+
+fn line_with_wrapper_to_html(buffer: &mut ToHtmlBuffer, line: &CodeLineHtml, line_numbers: bool) {
+    let class = if line.highlighted {
+        "line highlighted"
+    } else {
+        "line"
+    };
+    let number = line.number.to_string();
+    let mut attrs = vec![("class", class)];
+    if line_numbers {
+        attrs.push(("data-line", &number));
+    }
+
+    let tag = buffer.inline_tag("span", &attrs);
+    tag.buffer.buffer.push_str(line.html);
+}
+
+fn definition_to_html(_buffer: &mut ToHtmlBuffer, _node: ast::Definition) {
+    // Definitions are not displayed, references are resolved with them
 }
 
 fn delete_to_html(buffer: &mut ToHtmlBuffer, node: ast::Delete) {
-    let tag = buffer.tag("s", "");
+    let tag = buffer.inline_tag("del", &[]);
     children_to_html(tag.buffer, node.children)
 }
 
 fn emphasis_to_html(buffer: &mut ToHtmlBuffer, node: ast::Emphasis) {
-    let tag = buffer.tag("i", "");
+    let tag = buffer.inline_tag("em", &[]);
     children_to_html(tag.buffer, node.children);
 }
 
-fn footnote_definition_to_html(_buffer: &mut ToHtmlBuffer, node: ast::FootnoteDefinition) {
-    todo!("{node:#?}");
+fn footnote_definition_to_html(_buffer: &mut ToHtmlBuffer, _node: ast::FootnoteDefinition) {
+    // Definitions are rendered at the end of the document, see `footnotes_to_html`
 }
 
-fn footnote_reference_to_html(_buffer: &mut ToHtmlBuffer, node: ast::FootnoteReference) {
-    todo!("{node:#?}")
+fn footnote_reference_to_html(buffer: &mut ToHtmlBuffer, node: ast::FootnoteReference) {
+    if !buffer.footnotes.definitions.contains_key(&node.identifier) {
+        let label = node.label.as_deref().unwrap_or(&node.identifier);
+
+        buffer.warnings.push(Warning {
+            message: format!("Footnote [^{label}] is not defined"),
+            position: node.position.clone(),
+        });
+        buffer.write_text(&format!("[^{label}]"));
+        return;
+    }
+
+    let (number, reference) = buffer.footnotes.reference(&node.identifier);
+    let slug = buffer.footnotes.id(&node.identifier);
+
+    let id = format!("footnote-reference-{slug}-{reference}");
+    let href = format!("#footnote-{slug}");
+    let sup = buffer.inline_tag("sup", &[("class", "footnote-reference"), ("id", &id)]);
+    let tag = sup.buffer.inline_tag("a", &[("href", &href)]);
+    _ = write!(tag.buffer, "{number}");
+}
+
+/// Writes the referenced footnotes as a list, in the order they were first
+/// referenced and with links back to every reference
+fn footnotes_to_html(buffer: &mut ToHtmlBuffer) {
+    let document = std::mem::take(&mut buffer.buffer);
+
+    // Footnotes can reference other footnotes, which are appended to the order,
+    // so every content is rendered before knowing the references to each one
+    let mut contents = Vec::new();
+    let mut index = 0;
+
+    while let Some(identifier) = buffer.footnotes.order.get(index).cloned() {
+        let mut children = buffer.footnotes.definitions[&identifier].children.clone();
+
+        // The back-references are placed inside the last paragraph, if any
+        let last_paragraph = match children.last() {
+            Some(ast::Node::Paragraph(_)) => children.pop(),
+            _ => None,
+        };
+
+        children_to_html(buffer, children);
+
+        if let Some(ast::Node::Paragraph(paragraph)) = &last_paragraph {
+            _ = buffer.write_str("<p>");
+            children_to_html(buffer, paragraph.children.clone());
+        }
+
+        let content = std::mem::take(&mut buffer.buffer);
+        contents.push((identifier, content, last_paragraph.is_some()));
+        index += 1;
+    }
+
+    buffer.buffer = document;
+
+    for (identifier, definition) in &buffer.footnotes.definitions {
+        if !buffer.footnotes.references.contains_key(identifier) {
+            let label = definition.label.as_deref().unwrap_or(identifier);
+
+            buffer.warnings.push(Warning {
+                message: format!("Footnote [^{label}] is never referenced"),
+                position: definition.position.clone(),
+            });
+        }
+    }
+
+    if contents.is_empty() {
+        return;
+    }
+
+    let section = buffer.tag(
+        "section",
+        &[("class", "footnotes"), ("role", "doc-endnotes")],
+    );
+    section.buffer.void_tag("hr", &[]);
+    section.buffer.push_newline();
+
+    let list = section.buffer.tag("ol", &[]);
+    for (identifier, content, in_paragraph) in contents {
+        let slug = list.buffer.footnotes.id(&identifier);
+        let id = format!("footnote-{slug}");
+        let item = list.buffer.tag("li", &[("id", &id)]);
+        _ = item.buffer.write_str(&content);
+
+        let references = item.buffer.footnotes.references[&identifier];
+        for reference in 1..=references {
+            let href = format!("#footnote-reference-{slug}-{reference}");
+            let label = format!("Back to reference {reference}");
+            let attrs = [
+                ("href", href.as_str()),
+                ("class", "footnote-backref"),
+                ("role", "doc-backlink"),
+                ("aria-label", label.as_str()),
+            ];
+
+            _ = item.buffer.write_char(' ');
+            let backref = item.buffer.inline_tag("a", &attrs);
+            _ = backref.buffer.write_str("↩");
+            if reference > 1 {
+                let sup = backref.buffer.inline_tag("sup", &[]);
+                _ = write!(sup.buffer, "{reference}");
+            }
+        }
+
+        if in_paragraph {
+            _ = item.buffer.write_str("</p>");
+        }
+    }
 }
 
 fn heading_to_html(buffer: &mut ToHtmlBuffer, node: ast::Heading) {
+    let title = plain_text(&node.children);
+    let id = buffer.slugger.slug(&title);
+
+    buffer.headings.push(Heading {
+        depth: node.depth,
+        id: id.clone(),
+        title,
+    });
+
     let tag = format!("h{}", node.depth);
-    let tag = buffer.tag(&tag, "");
+    let tag = buffer.tag(&tag, &[("id", &id)]);
+
+    let href = format!("#{id}");
+    let attrs = [
+        ("class", "anchor"),
+        ("href", &href),
+        ("aria-label", "Link to this section"),
+    ];
+    let anchor = tag.buffer.inline_tag("a", &attrs);
+    _ = anchor.buffer.write_char('#');
+    anchor.close();
+
     children_to_html(tag.buffer, node.children);
 }
 
-fn html_to_html(_buffer: &mut ToHtmlBuffer, node: ast::Html) {
-    todo!("{node:#?}")
+/// Text content of phrasing nodes, as read by the user
+fn plain_text(nodes: &[ast::Node]) -> String {
+    let mut text = String::new();
+
+    for node in nodes {
+        match node {
+            ast::Node::Text(ast::Text { value, .. })
+            | ast::Node::InlineCode(ast::InlineCode { value, .. })
+            | ast::Node::InlineMath(ast::InlineMath { value, .. }) => text.push_str(value),
+            ast::Node::Image(ast::Image { alt, .. })
+            | ast::Node::ImageReference(ast::ImageReference { alt, .. }) => text.push_str(alt),
+            node => text.push_str(&plain_text(node.children().map_or(&[], Vec::as_slice))),
+        }
+    }
+
+    text
 }
 
-fn image_reference_to_html(_buffer: &mut ToHtmlBuffer, node: ast::ImageReference) {
-    todo!("{node:#?}")
+fn html_to_html(buffer: &mut ToHtmlBuffer, node: ast::Html) {
+    // Raw HTML is written as is, like other markdown renderers do
+    _ = buffer.write_str(&node.value);
+}
+
+fn image_reference_to_html(buffer: &mut ToHtmlBuffer, node: ast::ImageReference) {
+    let Some(definition) = buffer.definitions.get(&node.identifier) else {
+        log::warn!(
+            "No definition for the image reference [{}]",
+            node.identifier
+        );
+        buffer.write_text(&node.alt);
+        return;
+    };
+
+    let url = definition.url.clone();
+    let title = definition.title.clone();
+
+    let mut attrs = vec![("src", url.as_str()), ("alt", node.alt.as_str())];
+    if let Some(title) = &title {
+        attrs.push(("title", title));
+    }
+    buffer.void_tag("img", &attrs);
 }
 
 fn image_to_html(buffer: &mut ToHtmlBuffer, node: ast::Image) {
-    _ = write!(
-        buffer,
-        r#"<img src="{}" alt="{}" title="{}" />"#,
-        node.url,
-        node.alt,
-        node.title.unwrap_or(String::new())
-    );
+    let mut attrs = vec![("src", node.url.as_str()), ("alt", node.alt.as_str())];
+    if let Some(title) = &node.title {
+        attrs.push(("title", title));
+    }
+    buffer.void_tag("img", &attrs);
 }
 
 fn inline_code_to_html(buffer: &mut ToHtmlBuffer, node: ast::InlineCode) {
-    let tag = buffer.tag("code", "");
-    _ = tag.buffer.write_str(&node.value);
+    let tag = buffer.inline_tag("code", &[]);
+    tag.buffer.write_text(&node.value);
 }
 
-fn inline_math_to_html(_buffer: &mut ToHtmlBuffer, node: ast::InlineMath) {
-    todo!("{node:#?}")
+fn inline_math_to_html(buffer: &mut ToHtmlBuffer, node: ast::InlineMath) {
+    let tag = buffer.inline_tag("code", &[("class", "language-math math-inline")]);
+    tag.buffer.write_text(&node.value);
 }
 
-fn link_reference_to_html(_buffer: &mut ToHtmlBuffer, node: ast::LinkReference) {
-    todo!("{node:#?}")
+fn link_reference_to_html(buffer: &mut ToHtmlBuffer, node: ast::LinkReference) {
+    let Some(definition) = buffer.definitions.get(&node.identifier) else {
+        log::warn!("No definition for the link reference [{}]", node.identifier);
+        children_to_html(buffer, node.children);
+        return;
+    };
+
+    let url = definition.url.clone();
+    let title = definition.title.clone();
+
+    let mut attrs = vec![("href", url.as_str())];
+    if let Some(title) = &title {
+        attrs.push(("title", title));
+    }
+    let tag = buffer.inline_tag("a", &attrs);
+    children_to_html(tag.buffer, node.children);
 }
 
 fn link_to_html(buffer: &mut ToHtmlBuffer, node: ast::Link) {
-    let attrs = format!(
-        "href=\"{}\" title=\"{}\"",
-        node.url,
-        node.title.unwrap_or(String::new())
-    );
-    let tag = buffer.tag("a", &attrs);
+    let mut attrs = vec![("href", node.url.as_str())];
+    if let Some(title) = &node.title {
+        attrs.push(("title", title));
+    }
+    let tag = buffer.inline_tag("a", &attrs);
     children_to_html(tag.buffer, node.children);
 }
 
 fn list_item_to_html(buffer: &mut ToHtmlBuffer, node: ast::ListItem) {
-    let tag = buffer.tag("li", "");
-    children_to_html(tag.buffer, node.children);
+    let spread = node.spread;
+    list_item_with_spread_to_html(buffer, node, spread);
+}
+
+/// Items of tight lists don't wrap their paragraphs in `<p>`
+fn list_item_with_spread_to_html(buffer: &mut ToHtmlBuffer, node: ast::ListItem, spread: bool) {
+    let tag = buffer.tag("li", &[]);
+
+    if let Some(checked) = node.checked {
+        let mut attrs = vec![("type", "checkbox"), ("disabled", "")];
+        if checked {
+            attrs.push(("checked", ""));
+        }
+        tag.buffer.void_tag("input", &attrs);
+        _ = tag.buffer.write_char(' ');
+    }
+
+    for child in node.children {
+        match child {
+            ast::Node::Paragraph(paragraph) if !spread => {
+                children_to_html(tag.buffer, paragraph.children)
+            }
+            child => node_to_html(tag.buffer, child),
+        }
+    }
 }
 
 fn list_to_html(buffer: &mut ToHtmlBuffer, node: ast::List) {
-    let tag = buffer.tag(if node.ordered { "ol" } else { "ul" }, "");
-    children_to_html(tag.buffer, node.children);
+    let spread = node.spread
+        || node
+            .children
+            .iter()
+            .any(|child| matches!(child, ast::Node::ListItem(item) if item.spread));
+
+    let start = node
+        .start
+        .filter(|start| *start != 1)
+        .map(|start| start.to_string());
+    let attrs = match &start {
+        Some(start) => vec![("start", start.as_str())],
+        None => vec![],
+    };
+
+    let tag = buffer.tag(if node.ordered { "ol" } else { "ul" }, &attrs);
+    for child in node.children {
+        match child {
+            ast::Node::ListItem(item) => list_item_with_spread_to_html(tag.buffer, item, spread),
+            child => node_to_html(tag.buffer, child),
+        }
+    }
 }
 
-fn math_to_html(_buffer: &mut ToHtmlBuffer, node: ast::Math) {
-    todo!("{node:#?}")
+fn math_to_html(buffer: &mut ToHtmlBuffer, node: ast::Math) {
+    let pre = buffer.tag("pre", &[]);
+    let tag = pre
+        .buffer
+        .inline_tag("code", &[("class", "language-math math-display")]);
+    tag.buffer.write_text(&node.value);
 }
 
 fn mdx_flow_expression_to_html(_buffer: &mut ToHtmlBuffer, node: ast::MdxFlowExpression) {
-    todo!("{node:#?}")
+    // Expressions are JavaScript, which is not evaluated at build time
+    log::debug!("Skipping expression {{{}}}", node.value);
 }
 
 fn mdx_jsx_flow_element_to_html(buffer: &mut ToHtmlBuffer, node: ast::MdxJsxFlowElement) {
-    let Some(name) = &node.name else {
+    widget_to_html(
+        buffer,
+        "div",
+        node.name.as_deref(),
+        &node.attributes,
+        node.children,
+    );
+}
+
+fn mdx_jsx_text_element_to_html(buffer: &mut ToHtmlBuffer, node: ast::MdxJsxTextElement) {
+    widget_to_html(
+        buffer,
+        "span",
+        node.name.as_deref(),
+        &node.attributes,
+        node.children,
+    );
+}
+
+/// Widgets are mounted on their container by their script, which finds them by id
+///
+/// Static properties are passed as `data-*` attributes of the container, so they
+/// cannot replace its id or add event handlers. Expressions are JavaScript and
+/// are skipped, like the rest of expressions.
+fn widget_to_html(
+    buffer: &mut ToHtmlBuffer,
+    container: &str,
+    name: Option<&str>,
+    attributes: &[ast::AttributeContent],
+    children: Vec<ast::Node>,
+) {
+    let Some(name) = name else {
         log::error!("Fragments are not supported");
         return;
     };
 
-    let attrs = {
-        // TODO: attrs
-    };
-
     let id = {
         let mut id_ref = buffer.widgets.get_mut(name).unwrap_or_else(|| {
-            buffer.widgets.insert(name.clone(), 0);
+            buffer.widgets.insert(name.to_string(), 0);
             buffer.widgets.get_mut(name).unwrap()
         });
 
-        let id = id_ref.value().clone();
+        let id = *id_ref.value();
 
         *id_ref += 1;
 
         id
     };
 
-    let attrs = format!(r#"style="display: contents;" id="widget-{name}-{id}""#);
-    let tag = buffer.tag("div", &attrs);
+    let id = format!("widget-{name}-{id}");
+    let props = attributes
+        .iter()
+        .filter_map(|attribute| match attribute {
+            ast::AttributeContent::Property(ast::MdxJsxAttribute { name, value: None }) => {
+                Some((format!("data-{name}"), ""))
+            }
+            ast::AttributeContent::Property(ast::MdxJsxAttribute {
+                name,
+                value: Some(ast::AttributeValue::Literal(value)),
+            }) => Some((format!("data-{name}"), value.as_str())),
+            ast::AttributeContent::Property(ast::MdxJsxAttribute {
+                name,
+                value: Some(ast::AttributeValue::Expression(expression)),
+            }) => {
+                log::debug!("Skipping expression {name}={{{}}}", expression.value);
+                None
+            }
+            ast::AttributeContent::Expression(expression) => {
+                log::debug!("Skipping expression {{{}}}", expression.value);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
 
-    children_to_html(tag.buffer, node.children);
-}
+    let mut attrs = vec![("style", "display: contents;"), ("id", id.as_str())];
+    attrs.extend(props.iter().map(|(name, value)| (name.as_str(), *value)));
 
-fn mdx_jsx_text_element_to_html(_buffer: &mut ToHtmlBuffer, node: ast::MdxJsxTextElement) {
-    todo!("{node:#?}")
+    let tag = buffer.tag(container, &attrs);
+
+    children_to_html(tag.buffer, children);
 }
 
 fn mdx_text_expression_to_html(_buffer: &mut ToHtmlBuffer, node: ast::MdxTextExpression) {
-    todo!("{node:#?}")
+    // Expressions are JavaScript, which is not evaluated at build time
+    log::debug!("Skipping expression {{{}}}", node.value);
 }
 
 fn mdxjs_esm_to_html(_buffer: &mut ToHtmlBuffer, node: ast::MdxjsEsm) {
-    todo!("{node:#?}")
+    // Imports and exports are only meaningful to the JavaScript of the page
+    log::debug!("Skipping ESM block {}", node.value);
 }
 
 fn paragraph_to_html(buffer: &mut ToHtmlBuffer, node: ast::Paragraph) {
-    let tag = buffer.tag("p", "");
+    let tag = buffer.tag("p", &[]);
     children_to_html(tag.buffer, node.children);
 }
 
@@ -210,65 +657,730 @@ fn root_to_html(buffer: &mut ToHtmlBuffer, node: ast::Root) {
 }
 
 fn strong_to_html(buffer: &mut ToHtmlBuffer, node: ast::Strong) {
-    let tag = buffer.tag("strong", "");
+    let tag = buffer.inline_tag("strong", &[]);
     children_to_html(tag.buffer, node.children);
 }
 
 fn table_cell_to_html(buffer: &mut ToHtmlBuffer, node: ast::TableCell) {
-    let tag = buffer.tag("td", "");
+    table_cell_with_align_to_html(buffer, node, "td", &ast::AlignKind::None);
+}
+
+fn table_cell_with_align_to_html(
+    buffer: &mut ToHtmlBuffer,
+    node: ast::TableCell,
+    tag: &str,
+    align: &ast::AlignKind,
+) {
+    let style = match align {
+        ast::AlignKind::Left => Some("text-align: left"),
+        ast::AlignKind::Right => Some("text-align: right"),
+        ast::AlignKind::Center => Some("text-align: center"),
+        ast::AlignKind::None => None,
+    };
+    let attrs = match style {
+        Some(style) => vec![("style", style)],
+        None => vec![],
+    };
+
+    let tag = buffer.tag(tag, &attrs);
     children_to_html(tag.buffer, node.children);
 }
 
 fn table_row_to_html(buffer: &mut ToHtmlBuffer, node: ast::TableRow) {
-    let tag = buffer.tag("tr", "");
-    children_to_html(tag.buffer, node.children);
+    table_row_with_align_to_html(buffer, node, "td", &[]);
+}
+
+fn table_row_with_align_to_html(
+    buffer: &mut ToHtmlBuffer,
+    node: ast::TableRow,
+    cell_tag: &str,
+    align: &[ast::AlignKind],
+) {
+    let tag = buffer.tag("tr", &[]);
+    for (index, cell) in node.children.into_iter().enumerate() {
+        match cell {
+            ast::Node::TableCell(cell) => table_cell_with_align_to_html(
+                tag.buffer,
+                cell,
+                cell_tag,
+                align.get(index).unwrap_or(&ast::AlignKind::None),
+            ),
+            cell => node_to_html(tag.buffer, cell),
+        }
+    }
 }
 
 fn table_to_html(buffer: &mut ToHtmlBuffer, node: ast::Table) {
-    _ = buffer.tag("table", "");
-    if node.children.len() < 2 {
+    let mut rows = node.children.into_iter().filter_map(|row| match row {
+        ast::Node::TableRow(row) => Some(row),
+        _ => {
+            log::error!("Tables should only have rows");
+            None
+        }
+    });
+
+    let Some(head) = rows.next() else {
         log::error!("Tables should have at least one row");
         return;
-    }
-    let mut head = node.children;
-    let rows = head.split_off(1);
-    {
-        let head = head.into_iter().next().unwrap();
-        let head_row = if let ast::Node::TableRow(row) = head {
-            row
-        } else {
-            unreachable!();
-        };
-        _ = buffer.tag("thead", "");
-        _ = buffer.tag("tr", "");
-        for cell in head_row.children {
-            if let ast::Node::TableCell(cell) = cell {
-                let tag = buffer.tag("th", "");
-                children_to_html(tag.buffer, cell.children);
-            } else {
-                unreachable!();
-            }
+    };
+
+    let table = buffer.tag("table", &[]);
+
+    let thead = table.buffer.tag("thead", &[]);
+    table_row_with_align_to_html(thead.buffer, head, "th", &node.align);
+    thead.close();
+
+    let rows = rows.collect::<Vec<_>>();
+    if !rows.is_empty() {
+        let tbody = table.buffer.tag("tbody", &[]);
+        for row in rows {
+            table_row_with_align_to_html(tbody.buffer, row, "td", &node.align);
         }
     }
-    {
-        let tag = buffer.tag("tbody", "");
-        children_to_html(tag.buffer, rows);
-    }
 }
 
 fn text_to_html(buffer: &mut ToHtmlBuffer, node: ast::Text) {
-    _ = buffer.write_str(&node.value);
+    buffer.write_text(&node.value);
 }
 
 fn thematic_break_to_html(buffer: &mut ToHtmlBuffer, _node: ast::ThematicBreak) {
-    _ = buffer.write_str("<hr/>");
+    buffer.void_tag("hr", &[]);
     buffer.push_newline();
 }
 
-fn toml_to_html(_buffer: &mut ToHtmlBuffer, node: ast::Toml) {
-    todo!("{node:#?}")
+fn toml_to_html(_buffer: &mut ToHtmlBuffer, _node: ast::Toml) {
+    // Frontmatter is metadata of the chapter, not content
+}
+
+fn yaml_to_html(_buffer: &mut ToHtmlBuffer, _node: ast::Yaml) {
+    // Frontmatter is metadata of the chapter, not content
 }
 
-fn yaml_to_html(_buffer: &mut ToHtmlBuffer, node: ast::Yaml) {
-    todo!("{node:#?}")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(markdown: &str) -> String {
+        let node = markdown::to_mdast(markdown, &markdown::ParseOptions::gfm()).unwrap();
+
+        to_html(node, &Options::default()).content
+    }
+
+    /// Renders without beautify, so the output can be compared as a whole
+    fn golden(markdown: &str) -> String {
+        let options = markdown::ParseOptions {
+            constructs: markdown::Constructs {
+                frontmatter: true,
+                math_flow: true,
+                math_text: true,
+                ..markdown::Constructs::gfm()
+            },
+            ..markdown::ParseOptions::gfm()
+        };
+        let node = markdown::to_mdast(markdown, &options).unwrap();
+
+        super::render(node, &Options::default(), false).content
+    }
+
+    fn golden_mdx(mdx: &str) -> String {
+        let node = markdown::to_mdast(mdx, &markdown::ParseOptions::mdx()).unwrap();
+
+        super::render(node, &Options::default(), false).content
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let html = render(r"a < b && c > d \<script>alert(1)\</script>");
+
+        assert!(html.contains("a &lt; b &amp;&amp; c &gt; d &lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn code_is_escaped() {
+        let html = render("```\n</code><script>alert(1)</script>\n```");
+
+        assert!(html.contains("&lt;/code&gt;&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn highlighted_code_is_escaped() {
+        let html = render("```html\n</code><script>alert(1)</script>\n```");
+
+        assert!(html.contains("&lt;/"));
+        assert_eq!(html.matches("</code>").count(), 1);
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn code_language_cannot_break_the_attribute() {
+        let html = render("```\"onclick=\"alert(1)\n```");
+
+        assert!(html.contains(r#"class="language-&quot;onclick=&quot;alert(1)""#));
+    }
+
+    #[test]
+    fn inline_code_is_escaped() {
+        let html = render("`<img src=x onerror=alert(1)>`");
+
+        assert!(html.contains("&lt;img src=x onerror=alert(1)&gt;"));
+        assert!(!html.contains("<img"));
+    }
+
+    #[test]
+    fn link_attributes_are_escaped() {
+        let html = render(r#"[x](https://a.com "a\" onmouseover=\"alert(1)")"#);
+
+        assert!(html.contains(r#"href="https://a.com""#));
+        assert!(html.contains(r#"title="a&quot; onmouseover=&quot;alert(1)""#));
+    }
+
+    #[test]
+    fn link_url_cannot_break_the_attribute() {
+        let html = render(r#"[x](<https://a.com/"onmouseover="alert(1)>)"#);
+
+        assert!(!html.contains(r#"" onmouseover"#));
+        assert!(!html.contains(r#""onmouseover=""#));
+    }
+
+    #[test]
+    fn image_attributes_are_escaped() {
+        let html = render(r#"![" onerror="alert(1)](a.png "<b>'t'</b>")"#);
+
+        assert!(html.contains(r#"alt="&quot; onerror=&quot;alert(1)""#));
+        assert!(html.contains(r#"title="&lt;b&gt;&#39;t&#39;&lt;/b&gt;""#));
+    }
+
+    #[test]
+    fn link_without_title_has_no_title_attribute() {
+        let html = render("[x](https://a.com)");
+
+        assert!(!html.contains("title="));
+    }
+
+    #[test]
+    fn golden_blockquote() {
+        assert_eq!(golden("> quote"), "<blockquote><p>quote</p></blockquote>");
+    }
+
+    #[test]
+    fn golden_break() {
+        assert_eq!(golden("a\\\nb"), "<p>a<br />b</p>");
+        assert_eq!(golden("a  \nb"), "<p>a<br />b</p>");
+    }
+
+    #[test]
+    fn golden_code() {
+        assert_eq!(
+            golden("```\nfn main() {}\n```"),
+            r#"<pre class="hl-code"><code>fn main() {}</code></pre>"#
+        );
+    }
+
+    #[test]
+    fn code_is_highlighted_with_classes() {
+        let html = golden("```rust,ignore\nlet x = \"a\";\n```");
+
+        assert!(html.starts_with(r#"<pre class="hl-code"><code class="language-rust ignore">"#));
+        assert!(html.contains(r#"<span class="hl-storage hl-type hl-rust">let</span>"#));
+        assert!(html.contains(r#"<span class="hl-string hl-quoted hl-double hl-rust">"#));
+    }
+
+    #[test]
+    fn rust_code_has_the_edition_of_the_book() {
+        let options = Options {
+            edition: Some(RustEdition::E2024),
+            ..Options::default()
+        };
+        let render = |markdown| {
+            let node = markdown::to_mdast(markdown, &Default::default()).unwrap();
+            super::render(node, &options, false).content
+        };
+
+        assert!(render(
+            "```rust
+fn main() {}
+```"
+        )
+        .contains(r#"<code class="language-rust edition2024">"#));
+        assert!(render(
+            "```rust,edition2018
+fn main() {}
+```"
+        )
+        .contains(r#"<code class="language-rust edition2018">"#));
+        assert!(render(
+            "```toml
+a = 1
+```"
+        )
+        .contains(r#"<code class="language-toml">"#));
+    }
+
+    #[test]
+    fn highlighted_lines_are_balanced() {
+        let html = golden("```rust\n/* a\nb */\n```");
+        let lines = html.split('\n').collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            assert_eq!(
+                line.matches("<span").count(),
+                line.matches("</span>").count()
+            );
+        }
+    }
+
+    #[test]
+    fn code_lines_can_be_highlighted() {
+        let html = golden("```text {1, 3-4}\na\nb\nc\nd\ne\n```");
+
+        assert!(html.contains(concat!(
+            r#"<span class="line highlighted">a</span>"#,
+            "\nb\n",
+            r#"<span class="line highlighted">c</span>"#,
+            "\n",
+            r#"<span class="line highlighted">d</span>"#,
+            "\ne</code>",
+        )));
+    }
+
+    #[test]
+    fn code_lines_can_be_numbered() {
+        let node = markdown::to_mdast("```\na\nb\n```", &Default::default()).unwrap();
+        let options = Options {
+            line_numbers: true,
+            ..Options::default()
+        };
+        let html = super::render(node, &options, false).content;
+
+        assert_eq!(
+            html,
+            concat!(
+                r#"<pre class="hl-code line-numbers"><code>"#,
+                r#"<span class="line" data-line="1">a</span>"#,
+                "\n",
+                r#"<span class="line" data-line="2">b</span>"#,
+                "</code></pre>",
+            )
+        );
+    }
+
+    #[test]
+    fn rust_hidden_lines() {
+        let html = golden("```rust\n# fn main() {\n#[derive(Debug)]\n##[doc]\n  #\n# }\n```");
+
+        assert_eq!(html.matches(r#"<span class="boring">"#).count(), 3);
+        assert!(html.contains("derive"));
+        assert!(!html.contains("##"));
+        assert!(!html.contains("# fn"));
+        assert!(html.ends_with("}</span></span></span></span></span></code></pre>"));
+    }
+
+    #[test]
+    fn hidden_lines_keep_the_code() {
+        let lines = split_hidden_lines("# use a;\n  # b\n##c\nd", Some("rust"), None);
+        let code = lines
+            .iter()
+            .map(|line| line.text.as_ref())
+            .collect::<Vec<_>>();
+        let hidden = lines.iter().map(|line| line.hidden).collect::<Vec<_>>();
+
+        assert_eq!(code, ["use a;", "  b", "#c", "d"]);
+        assert_eq!(hidden, [true, true, false, false]);
+    }
+
+    #[test]
+    fn hidden_lines_use_the_configured_prefix() {
+        let node = markdown::to_mdast("```custom\n~a\nb\n```", &Default::default()).unwrap();
+        let options = Options {
+            hidelines: HashMap::from([("custom".to_string(), "~".to_string())]),
+            ..Options::default()
+        };
+        let html = super::render(node, &options, false).content;
+
+        assert!(html.contains("<span class=\"boring\">a\n</span>b"));
+    }
+
+    #[test]
+    fn hidden_lines_prefix_can_be_an_attribute() {
+        let html = golden("```python,hidelines=!!!\n!!!import os\n# comment\n```");
+
+        assert_eq!(html.matches(r#"<span class="boring">"#).count(), 1);
+        assert!(html.contains("comment"));
+    }
+
+    #[test]
+    fn golden_definition() {
+        assert_eq!(golden("[a]: https://a.com"), "");
+    }
+
+    #[test]
+    fn golden_delete() {
+        assert_eq!(golden("~~gone~~"), "<p><del>gone</del></p>");
+    }
+
+    #[test]
+    fn golden_emphasis() {
+        assert_eq!(golden("*a* b"), "<p><em>a</em> b</p>");
+    }
+
+    #[test]
+    fn golden_footnotes() {
+        assert_eq!(
+            golden("a[^1]\n\n[^1]: note"),
+            concat!(
+                r#"<p>a<sup class="footnote-reference" id="footnote-reference-1-1">"#,
+                r##"<a href="#footnote-1">1</a></sup></p>"##,
+                r#"<section class="footnotes" role="doc-endnotes"><hr /><ol>"#,
+                r#"<li id="footnote-1"><p>note "#,
+                r##"<a href="#footnote-reference-1-1" class="footnote-backref" "##,
+                r#"role="doc-backlink" aria-label="Back to reference 1">↩</a></p></li>"#,
+                "</ol></section>"
+            )
+        );
+    }
+
+    #[test]
+    fn footnotes_are_numbered_by_first_reference() {
+        let html = golden("a[^z] b[^a]\n\n[^a]: first\n[^z]: second");
+
+        assert!(html.contains(r##"<a href="#footnote-z">1</a>"##));
+        assert!(html.contains(r##"<a href="#footnote-a">2</a>"##));
+        assert!(html.find(r#"id="footnote-z""#) < html.find(r#"id="footnote-a""#));
+    }
+
+    #[test]
+    fn footnotes_link_back_to_every_reference() {
+        let html = golden("a[^n] b[^n]\n\n[^n]: note");
+
+        assert!(html.contains(r#"id="footnote-reference-n-1""#));
+        assert!(html.contains(r#"id="footnote-reference-n-2""#));
+        assert!(html.contains(r##"<a href="#footnote-reference-n-1""##));
+        assert!(html.contains("↩<sup>2</sup></a>"));
+        // Both references show the number of the footnote
+        assert_eq!(html.matches(r##"<a href="#footnote-n">1</a>"##).count(), 2);
+    }
+
+    #[test]
+    fn footnote_problems_are_returned_with_their_position() {
+        let node = markdown::to_mdast(
+            "a[^used]\n\n[^b]: note\n[^used]: note\n[^a]: note\n",
+            &markdown::ParseOptions::gfm(),
+        )
+        .unwrap();
+        let html = to_html(node, &Options::default());
+
+        let warnings = html
+            .warnings
+            .iter()
+            .map(|warning| {
+                let start = &warning.position.as_ref().unwrap().start;
+                (warning.message.as_str(), start.line, start.column)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            warnings,
+            [
+                ("Footnote [^b] is never referenced", 3, 1),
+                ("Footnote [^a] is never referenced", 5, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn footnote_ids_are_slugs() {
+        let html =
+            golden("a[^A/b?] b[^ab] c[^\"q\"#]\n\n[^A/b?]: one\n[^ab]: two\n[^\"q\"#]: three");
+
+        assert!(html.contains(r#"id="footnote-reference-ab-1""#));
+        assert!(html.contains(r##"<a href="#footnote-ab">1</a>"##));
+        assert!(html.contains(r#"<li id="footnote-ab">"#));
+        assert!(html.contains(r##"<a href="#footnote-reference-ab-1" class="footnote-backref""##));
+        // Identifiers with the same slug get different IDs
+        assert!(html.contains(r##"<a href="#footnote-ab-1">2</a>"##));
+        assert!(html.contains(r#"<li id="footnote-ab-1">"#));
+        assert!(html.contains(r##"<a href="#footnote-q">3</a>"##));
+        assert!(html.contains(r#"<li id="footnote-q">"#));
+    }
+
+    #[test]
+    fn footnotes_can_reference_footnotes() {
+        let html = golden("a[^1]\n\n[^1]: see[^2]\n[^2]: nested");
+
+        assert!(html.contains(r##"<a href="#footnote-2">2</a>"##));
+        assert!(html.find(r#"id="footnote-1""#) < html.find(r#"id="footnote-2""#));
+    }
+
+    #[test]
+    fn footnotes_without_paragraph_have_back_references() {
+        let html = golden("a[^1]\n\n[^1]:\n    ```\n    code\n    ```");
+
+        assert!(html.contains(
+            r##"<pre class="hl-code"><code>code</code></pre> <a href="#footnote-reference-1-1""##
+        ));
+        assert!(!html.contains("</a></p></li>"));
+    }
+
+    #[test]
+    fn undefined_footnotes_are_kept_as_text() {
+        assert_eq!(golden("a[^missing]"), "<p>a[^missing]</p>");
+    }
+
+    #[test]
+    fn unused_footnotes_are_not_rendered() {
+        assert_eq!(golden("a\n\n[^1]: unused"), "<p>a</p>");
+    }
+
+    #[test]
+    fn golden_heading() {
+        assert_eq!(
+            golden("## Title"),
+            r##"<h2 id="title"><a class="anchor" href="#title" aria-label="Link to this section">#</a>Title</h2>"##
+        );
+    }
+
+    fn heading_ids(markdown: &str) -> Vec<String> {
+        let node = markdown::to_mdast(markdown, &markdown::ParseOptions::gfm()).unwrap();
+
+        to_html(node, &Options::default())
+            .headings
+            .into_iter()
+            .map(|heading| heading.id)
+            .collect()
+    }
+
+    #[test]
+    fn heading_ids_are_github_slugs() {
+        assert_eq!(
+            heading_ids("# Hello, World!\n## `Option<T>` and *more*\n## snake_case - kebab"),
+            ["hello-world", "optiont-and-more", "snake_case---kebab"]
+        );
+    }
+
+    #[test]
+    fn heading_ids_keep_unicode_letters() {
+        assert_eq!(
+            heading_ids("# ¿Qué es Rust?\n## Año 2024: ñandú\n## Ownership (Propiedad)"),
+            ["qué-es-rust", "año-2024-ñandú", "ownership-propiedad"]
+        );
+    }
+
+    #[test]
+    fn heading_ids_are_unique_in_the_page() {
+        assert_eq!(
+            heading_ids("# A\n## A\n## A 1\n## A\n## !!!"),
+            ["a", "a-1", "a-1-1", "a-2", "heading"]
+        );
+    }
+
+    #[test]
+    fn heading_titles_are_plain_text() {
+        let node = markdown::to_mdast("## A [link](x) `code` &amp; ![img](y)", &Default::default());
+        let headings = to_html(node.unwrap(), &Options::default()).headings;
+
+        assert_eq!(headings[0].title, "A link code & img");
+        assert_eq!(headings[0].depth, 2);
+    }
+
+    #[test]
+    fn golden_html() {
+        assert_eq!(
+            golden("<div class=\"x\">\n\ntext\n\n</div>"),
+            r#"<div class="x"><p>text</p></div>"#
+        );
+        assert_eq!(golden("a <kbd>b</kbd>"), "<p>a <kbd>b</kbd></p>");
+    }
+
+    #[test]
+    fn golden_image() {
+        assert_eq!(
+            golden(r#"![alt](a.png "title")"#),
+            r#"<p><img src="a.png" alt="alt" title="title" /></p>"#
+        );
+    }
+
+    #[test]
+    fn golden_image_reference() {
+        assert_eq!(
+            golden("![alt][logo]\n\n[logo]: logo.png \"Logo\""),
+            r#"<p><img src="logo.png" alt="alt" title="Logo" /></p>"#
+        );
+    }
+
+    #[test]
+    fn golden_inline_code() {
+        assert_eq!(golden("`a`"), "<p><code>a</code></p>");
+    }
+
+    #[test]
+    fn golden_inline_math() {
+        assert_eq!(
+            golden("$x^2$"),
+            r#"<p><code class="language-math math-inline">x^2</code></p>"#
+        );
+    }
+
+    #[test]
+    fn golden_link() {
+        assert_eq!(
+            golden("[a](https://a.com)"),
+            r#"<p><a href="https://a.com">a</a></p>"#
+        );
+    }
+
+    #[test]
+    fn golden_link_reference() {
+        assert_eq!(
+            golden(
+                "[full][Site], [collapsed][] and [site]\n\n[site]: https://a.com\n[collapsed]: /c"
+            ),
+            concat!(
+                r#"<p><a href="https://a.com">full</a>, "#,
+                r#"<a href="/c">collapsed</a> and "#,
+                r#"<a href="https://a.com">site</a></p>"#
+            )
+        );
+    }
+
+    #[test]
+    fn golden_list() {
+        assert_eq!(golden("- a\n- b"), "<ul><li>a</li><li>b</li></ul>");
+        assert_eq!(
+            golden("3. a\n4. b"),
+            r#"<ol start="3"><li>a</li><li>b</li></ol>"#
+        );
+        assert_eq!(
+            golden("- a\n\n- b"),
+            "<ul><li><p>a</p></li><li><p>b</p></li></ul>"
+        );
+    }
+
+    #[test]
+    fn golden_list_item_task() {
+        assert_eq!(
+            golden("- [x] done\n- [ ] todo"),
+            concat!(
+                r#"<ul><li><input type="checkbox" disabled="" checked="" /> done</li>"#,
+                r#"<li><input type="checkbox" disabled="" /> todo</li></ul>"#
+            )
+        );
+    }
+
+    #[test]
+    fn golden_math() {
+        assert_eq!(
+            golden("$$\nx < y\n$$"),
+            r#"<pre><code class="language-math math-display">x &lt; y</code></pre>"#
+        );
+    }
+
+    #[test]
+    fn golden_mdx_expressions() {
+        assert_eq!(golden_mdx("{/* comment */}"), "");
+        assert_eq!(golden_mdx("a {1 + 1} b"), "<p>a  b</p>");
+    }
+
+    #[test]
+    fn golden_mdx_jsx_flow_element() {
+        assert_eq!(
+            golden_mdx("<Counter start={0} />\n\n<Counter />"),
+            concat!(
+                r#"<div style="display: contents;" id="widget-Counter-0"></div>"#,
+                r#"<div style="display: contents;" id="widget-Counter-1"></div>"#
+            )
+        );
+    }
+
+    #[test]
+    fn golden_mdx_jsx_attributes() {
+        assert_eq!(
+            golden_mdx(
+                r#"<Counter label="a &quot; b" id="x" onclick="alert(1)" hidden start={0} {...props} />"#
+            ),
+            concat!(
+                r#"<div style="display: contents;" id="widget-Counter-0" data-label="a &quot; b" "#,
+                r#"data-id="x" data-onclick="alert(1)" data-hidden=""></div>"#
+            )
+        );
+    }
+
+    #[test]
+    fn golden_mdx_jsx_text_element() {
+        assert_eq!(
+            golden_mdx("a <Badge>new</Badge>"),
+            r#"<p>a <span style="display: contents;" id="widget-Badge-0">new</span></p>"#
+        );
+    }
+
+    #[test]
+    fn golden_mdxjs_esm() {
+        // The parser only produces ESM nodes with a JavaScript parser configured
+        let node = ast::Node::MdxjsEsm(ast::MdxjsEsm {
+            value: "import Counter from './Counter.js'".to_string(),
+            position: None,
+            stops: vec![],
+        });
+
+        assert_eq!(super::render(node, &Options::default(), false).content, "");
+    }
+
+    #[test]
+    fn golden_paragraph() {
+        assert_eq!(golden("a\nb\n\nc"), "<p>a\nb</p><p>c</p>");
+    }
+
+    #[test]
+    fn golden_root() {
+        assert_eq!(golden(""), "");
+    }
+
+    #[test]
+    fn golden_strong() {
+        assert_eq!(golden("**a**b"), "<p><strong>a</strong>b</p>");
+    }
+
+    #[test]
+    fn golden_table() {
+        assert_eq!(
+            golden("| a | b | c |\n| :- | :-: | -: |\n| 1 | 2 | 3 |"),
+            concat!(
+                "<table><thead><tr>",
+                r#"<th style="text-align: left">a</th>"#,
+                r#"<th style="text-align: center">b</th>"#,
+                r#"<th style="text-align: right">c</th>"#,
+                "</tr></thead><tbody><tr>",
+                r#"<td style="text-align: left">1</td>"#,
+                r#"<td style="text-align: center">2</td>"#,
+                r#"<td style="text-align: right">3</td>"#,
+                "</tr></tbody></table>"
+            )
+        );
+    }
+
+    #[test]
+    fn golden_table_without_body() {
+        assert_eq!(
+            golden("| a |\n| - |"),
+            "<table><thead><tr><th>a</th></tr></thead></table>"
+        );
+    }
+
+    #[test]
+    fn golden_text() {
+        assert_eq!(golden("a & b"), "<p>a &amp; b</p>");
+    }
+
+    #[test]
+    fn golden_thematic_break() {
+        assert_eq!(golden("***"), "<hr />");
+    }
+
+    #[test]
+    fn golden_frontmatter() {
+        assert_eq!(golden("---\ntitle: a\n---\n\ntext"), "<p>text</p>");
+        assert_eq!(golden("+++\ntitle = \"a\"\n+++\n\ntext"), "<p>text</p>");
+    }
 }
@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+/// Information string of a fenced code block, like `rust,ignore {1,3-5}`
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CodeInfo {
+    /// Language used to highlight the code
+    pub language: Option<String>,
+    /// Attributes that follow the language, like `ignore` or `edition2021`
+    pub attributes: Vec<String>,
+    /// Numbers of the lines to highlight, starting from 1
+    pub highlighted_lines: HashSet<usize>,
+}
+
+impl CodeInfo {
+    /// Parses the `lang` and `meta` of a code node, which markdown splits at
+    /// the first whitespace
+    pub fn parse(lang: Option<&str>, meta: Option<&str>) -> Self {
+        let mut info = [lang, meta]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let mut highlighted_lines = HashSet::new();
+
+        // The ranges contain commas, so they are taken out before splitting
+        // the attributes
+        if let Some(start) = info.find('{') {
+            if let Some(end) = info[start..].find('}').map(|end| start + end) {
+                highlighted_lines = parse_line_ranges(&info[start + 1..end]);
+                info.replace_range(start..=end, " ");
+            }
+        }
+
+        let mut words = info
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|word| !word.is_empty())
+            .map(str::to_string);
+
+        Self {
+            language: words.next(),
+            attributes: words.collect(),
+            highlighted_lines,
+        }
+    }
+
+    /// Edition set by an `editionXXXX` attribute, like `2024`
+    pub fn edition(&self) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find_map(|attribute| attribute.strip_prefix("edition"))
+    }
+
+    /// Prefix of the hidden lines set by a `hidelines=<prefix>` attribute
+    pub fn hidelines(&self) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find_map(|attribute| attribute.strip_prefix("hidelines="))
+    }
+}
+
+/// Line of a code block, without the prefix that marks it as hidden
+#[derive(Debug, PartialEq, Eq)]
+pub struct CodeLine<'a> {
+    pub text: Cow<'a, str>,
+    pub hidden: bool,
+}
+
+/// Finds the hidden lines of a code block.
+///
+/// Lines starting with `prefix` are hidden. Without one, Rust code uses the
+/// rules of rustdoc: `# ` or a lone `#` hide the line and `##` is an escaped `#`
+pub fn split_hidden_lines<'a>(
+    code: &'a str,
+    language: Option<&str>,
+    prefix: Option<&str>,
+) -> Vec<CodeLine<'a>> {
+    let is_rust = prefix.is_none() && language == Some("rust");
+
+    code.lines()
+        .map(|line| {
+            let content = line.trim_start();
+            let indent = &line[..line.len() - content.len()];
+
+            let visible = CodeLine {
+                text: Cow::Borrowed(line),
+                hidden: false,
+            };
+            let hidden = |rest: &str| CodeLine {
+                text: Cow::Owned(format!("{indent}{rest}")),
+                hidden: true,
+            };
+
+            match prefix {
+                Some(prefix) => content.strip_prefix(prefix).map_or(visible, hidden),
+                None if !is_rust => visible,
+                None if content.starts_with("##") => CodeLine {
+                    text: Cow::Owned(format!("{indent}{}", &content[1..])),
+                    hidden: false,
+                },
+                None if content == "#" => hidden(""),
+                None => content.strip_prefix("# ").map_or(visible, hidden),
+            }
+        })
+        .collect()
+}
+
+/// Parses a list of lines and ranges like `1,3-5`, ignoring the invalid ones
+fn parse_line_ranges(ranges: &str) -> HashSet<usize> {
+    let mut lines = HashSet::new();
+
+    for range in ranges
+        .split(',')
+        .map(str::trim)
+        .filter(|range| !range.is_empty())
+    {
+        let parsed = match range.split_once('-') {
+            Some((start, end)) => start
+                .trim()
+                .parse::<usize>()
+                .and_then(|start| Ok((start, end.trim().parse::<usize>()?))),
+            None => range.parse::<usize>().map(|line| (line, line)),
+        };
+
+        match parsed {
+            Ok((start, end)) if start <= end => lines.extend(start..=end),
+            _ => log::warn!("Invalid range of lines to highlight: {range:?}"),
+        }
+    }
+
+    lines
+}
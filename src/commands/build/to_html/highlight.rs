@@ -0,0 +1,126 @@
+use std::sync::LazyLock;
+
+use syntect::highlighting::ThemeSet;
+use syntect::html::{css_for_theme_with_class_style, line_tokens_to_classed_spans, ClassStyle};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+use super::utils::escape_text;
+
+/// Prefix of the classes of the highlighted tokens, to not collide with the
+/// classes of the theme
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+/// Syntect theme used for each color theme of the book
+const THEMES: [(&str, &str); 5] = [
+    ("light", "InspiredGitHub"),
+    ("rust", "Solarized (light)"),
+    ("coal", "base16-eighties.dark"),
+    ("navy", "base16-ocean.dark"),
+    ("ayu", "base16-mocha.dark"),
+];
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+/// Highlights `code` as `language`, returning the HTML of each line without
+/// its line ending.
+///
+/// Every line is balanced, so each one can be wrapped in its own element
+pub fn highlight(code: &str, language: Option<&str>) -> Vec<String> {
+    let syntax = language.and_then(find_syntax);
+
+    let Some(syntax) = syntax else {
+        if let Some(language) = language {
+            log::debug!("No syntax to highlight {language:?}, using plain text");
+        }
+
+        return plain_lines(code);
+    };
+
+    let mut state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    let mut lines = Vec::new();
+
+    for line in LinesWithEndings::from(code) {
+        // Scopes that are still open from the previous line, like in a
+        // multiline comment, are opened again
+        let mut html = String::new();
+        for scope in stack.as_slice() {
+            html.push_str(r#"<span class=""#);
+            html.push_str(&scope_classes(&scope.build_string()));
+            html.push_str(r#"">"#);
+        }
+
+        let highlighted = state
+            .parse_line(line, &SYNTAXES)
+            .map_err(|err| err.to_string())
+            .and_then(|ops| {
+                line_tokens_to_classed_spans(line, &ops, CLASS_STYLE, &mut stack)
+                    .map_err(|err| err.to_string())
+            });
+
+        match highlighted {
+            // The syntaxes need the line ending, but it is not part of the line
+            Ok((spans, _)) => html.push_str(&spans.replace(['\n', '\r'], "")),
+            Err(err) => {
+                log::warn!("Cannot highlight the code as {language:?}.\n  Cause: {err}");
+                return plain_lines(code);
+            }
+        }
+
+        html.push_str(&"</span>".repeat(stack.len()));
+        lines.push(html);
+    }
+
+    lines
+}
+
+/// Stylesheet with the colors of the highlighted tokens for each color theme
+pub fn theme_css() -> String {
+    let themes = ThemeSet::load_defaults();
+    let mut css = String::new();
+
+    for (class, name) in THEMES {
+        let theme = &themes.themes[name];
+        let theme_css = css_for_theme_with_class_style(theme, CLASS_STYLE)
+            .expect("The builtin themes generate valid CSS");
+
+        // Rules are scoped to the class the color theme sets on `<html>`
+        for line in theme_css.lines() {
+            match line.strip_suffix(" {") {
+                Some(selectors) => {
+                    let selectors = selectors
+                        .split(", ")
+                        .map(|selector| format!(".{class} {selector}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    css.push_str(&selectors);
+                    css.push_str(" {");
+                }
+                None => css.push_str(line),
+            }
+            css.push('\n');
+        }
+    }
+
+    css
+}
+
+fn plain_lines(code: &str) -> Vec<String> {
+    code.lines()
+        .map(|line| escape_text(line).into_owned())
+        .collect()
+}
+
+fn find_syntax(language: &str) -> Option<&'static SyntaxReference> {
+    SYNTAXES.find_syntax_by_token(language)
+}
+
+/// Classes of a scope like `source.rust`, same as the ones written by syntect
+fn scope_classes(scope: &str) -> String {
+    scope
+        .split('.')
+        .map(|atom| format!("hl-{atom}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
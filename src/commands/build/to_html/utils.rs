@@ -1,20 +1,157 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt::Write;
 
+use markdown::mdast as ast;
+use markdown::unist::Position;
+
+use super::Options;
+
 pub struct ToHtmlBuffer {
     pub buffer: String,
+    pub options: Options,
     beautify: bool,
     indent: u8,
 
     pub widgets: dashmap::DashMap<String, usize>,
+    /// Link and image definitions of the document, by identifier
+    pub definitions: HashMap<String, Definition>,
+    pub footnotes: Footnotes,
+    /// Headings of the document, in the order they appear
+    pub headings: Vec<Heading>,
+    pub slugger: Slugger,
+    pub warnings: Vec<Warning>,
+}
+
+pub struct Definition {
+    pub url: String,
+    pub title: Option<String>,
+}
+
+/// Footnotes of the document, numbered in the order they are first referenced
+#[derive(Default)]
+pub struct Footnotes {
+    pub definitions: HashMap<String, ast::FootnoteDefinition>,
+    /// Identifiers of the referenced footnotes
+    pub order: Vec<String>,
+    /// Number of references to each footnote
+    pub references: HashMap<String, usize>,
+    /// Slugs of the identifiers, which can contain spaces or punctuation
+    slugs: HashMap<String, String>,
+    slugger: Slugger,
+}
+
+impl Footnotes {
+    /// Registers a reference, returning the number of the footnote and of the
+    /// reference to it
+    pub fn reference(&mut self, identifier: &str) -> (usize, usize) {
+        let number = match self.order.iter().position(|id| id == identifier) {
+            Some(index) => index + 1,
+            None => {
+                self.order.push(identifier.to_string());
+                self.order.len()
+            }
+        };
+
+        let references = self.references.entry(identifier.to_string()).or_default();
+        *references += 1;
+
+        (number, *references)
+    }
+
+    /// ID of a footnote, valid in HTML and unique in the document
+    pub fn id(&mut self, identifier: &str) -> String {
+        if let Some(slug) = self.slugs.get(identifier) {
+            return slug.clone();
+        }
+
+        let slug = self.slugger.slug_or(identifier, "note");
+        self.slugs.insert(identifier.to_string(), slug.clone());
+
+        slug
+    }
+}
+
+/// Heading of the document, used to build the table of contents of the page
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Heading {
+    pub depth: u8,
+    pub id: String,
+    /// Text content of the heading, without markup
+    pub title: String,
+}
+
+/// Problem found in the document, reported by the caller that knows its file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    pub message: String,
+    pub position: Option<Position>,
+}
+
+/// Generates the same IDs as GitHub for the headings of a document
+#[derive(Default)]
+pub struct Slugger {
+    /// Number of times each slug was generated
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    /// Returns a slug for `text` that was not returned before, adding a
+    /// numeric suffix to repeated ones
+    pub fn slug(&mut self, text: &str) -> String {
+        self.slug_or(text, "heading")
+    }
+
+    /// Like [`Slugger::slug`], using `fallback` for texts without letters or
+    /// numbers
+    pub fn slug_or(&mut self, text: &str, fallback: &str) -> String {
+        let mut slug = slugify(text);
+        if slug.is_empty() {
+            slug.push_str(fallback);
+        }
+
+        let original = slug.clone();
+
+        // A suffixed slug can be taken too, by a heading like `A 1`
+        while self.occurrences.contains_key(&slug) {
+            let occurrences = self.occurrences.entry(original.clone()).or_default();
+            *occurrences += 1;
+            slug = format!("{original}-{occurrences}");
+        }
+
+        self.occurrences.insert(slug.clone(), 0);
+
+        slug
+    }
+}
+
+/// Lowercases the text, drops the punctuation and replaces the whitespace
+/// with dashes, keeping any Unicode letter or number
+fn slugify(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_whitespace() => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
 }
 
 impl ToHtmlBuffer {
-    pub fn new(beautify: bool) -> Self {
+    pub fn new(options: Options, beautify: bool) -> Self {
         Self {
             buffer: String::new(),
+            options,
             beautify,
             indent: 0,
             widgets: dashmap::DashMap::new(),
+            definitions: HashMap::new(),
+            footnotes: Footnotes::default(),
+            headings: Vec::new(),
+            slugger: Slugger::default(),
+            warnings: Vec::new(),
         }
     }
 
@@ -37,8 +174,36 @@ impl ToHtmlBuffer {
         }
     }
 
-    pub fn tag<'a>(&'a mut self, tag: &'a str, attrs: &str) -> Tag<'a> {
-        Tag::open(self, tag, attrs)
+    /// Writes text content, escaping the characters that could be parsed as markup
+    pub fn write_text(&mut self, text: &str) {
+        self.buffer.push_str(&escape_text(text));
+    }
+
+    /// Opens a tag that is closed when the returned [`Tag`] is dropped.
+    ///
+    /// Attribute values are escaped, so they can come from the user content
+    pub fn tag<'a>(&'a mut self, tag: &'a str, attrs: &[(&str, &str)]) -> Tag<'a> {
+        Tag::open(self, tag, attrs, false)
+    }
+
+    /// Like [`ToHtmlBuffer::tag`], but never beautified, as the whitespace
+    /// inside phrasing content is visible
+    pub fn inline_tag<'a>(&'a mut self, tag: &'a str, attrs: &[(&str, &str)]) -> Tag<'a> {
+        Tag::open(self, tag, attrs, true)
+    }
+
+    /// Writes a tag without content, like `<img />`
+    pub fn void_tag(&mut self, tag: &str, attrs: &[(&str, &str)]) {
+        _ = self.write_char('<');
+        _ = self.write_str(tag);
+        self.write_attrs(attrs);
+        _ = self.write_str(" />");
+    }
+
+    fn write_attrs(&mut self, attrs: &[(&str, &str)]) {
+        for (name, value) in attrs {
+            _ = write!(self.buffer, r#" {name}="{}""#, escape_attr(value));
+        }
     }
 }
 
@@ -51,20 +216,31 @@ impl Write for ToHtmlBuffer {
 pub struct Tag<'a> {
     pub buffer: &'a mut ToHtmlBuffer,
     tag: &'a str,
+    inline: bool,
 }
 
 impl Tag<'_> {
-    pub fn open<'a>(buffer: &'a mut ToHtmlBuffer, tag: &'a str, attrs: &str) -> Tag<'a> {
+    pub fn open<'a>(
+        buffer: &'a mut ToHtmlBuffer,
+        tag: &'a str,
+        attrs: &[(&str, &str)],
+        inline: bool,
+    ) -> Tag<'a> {
         _ = buffer.write_char('<');
         _ = buffer.write_str(tag);
-        _ = buffer.write_char(' ');
-        _ = buffer.write_str(attrs);
+        buffer.write_attrs(attrs);
         _ = buffer.write_char('>');
 
-        buffer.push_indent();
-        buffer.push_newline();
+        if !inline {
+            buffer.push_indent();
+            buffer.push_newline();
+        }
 
-        Tag { buffer, tag }
+        Tag {
+            buffer,
+            tag,
+            inline,
+        }
     }
 
     pub fn close(self) {
@@ -74,8 +250,10 @@ impl Tag<'_> {
 
 impl Drop for Tag<'_> {
     fn drop(&mut self) {
-        self.buffer.pop_indent();
-        self.buffer.push_newline();
+        if !self.inline {
+            self.buffer.pop_indent();
+            self.buffer.push_newline();
+        }
 
         _ = self.buffer.write_char('<');
         _ = self.buffer.write_char('/');
@@ -83,3 +261,96 @@ impl Drop for Tag<'_> {
         _ = self.buffer.write_char('>');
     }
 }
+
+/// Escapes a value to be used as text content of an element
+pub fn escape_text(value: &str) -> Cow<'_, str> {
+    escape(value, false)
+}
+
+/// Escapes a value to be used inside a quoted attribute
+pub fn escape_attr(value: &str) -> Cow<'_, str> {
+    escape(value, true)
+}
+
+fn escape(value: &str, attr: bool) -> Cow<'_, str> {
+    let needs_escape = |c: char| matches!(c, '&' | '<' | '>') || attr && matches!(c, '"' | '\'');
+
+    if !value.contains(needs_escape) {
+        return Cow::Borrowed(value);
+    }
+
+    let mut escaped = String::with_capacity(value.len() + 16);
+
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if attr => escaped.push_str("&quot;"),
+            '\'' if attr => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_text_keeps_quotes() {
+        assert_eq!(
+            escape_text(r#"<a href="x">Tom & 'Jerry'</a>"#),
+            r#"&lt;a href="x"&gt;Tom &amp; 'Jerry'&lt;/a&gt;"#
+        );
+    }
+
+    #[test]
+    fn escape_attr_escapes_quotes() {
+        assert_eq!(
+            escape_attr(r#"" onerror='alert(1)' x=""#),
+            "&quot; onerror=&#39;alert(1)&#39; x=&quot;"
+        );
+    }
+
+    #[test]
+    fn escape_borrows_safe_values() {
+        assert!(matches!(escape_text("plain text"), Cow::Borrowed(_)));
+        assert!(matches!(escape_attr("language-rust"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn escape_text_escapes_existing_entities() {
+        assert_eq!(escape_text("&amp;"), "&amp;amp;");
+    }
+
+    #[test]
+    fn tag_escapes_attribute_values() {
+        let mut buffer = ToHtmlBuffer::new(Options::default(), false);
+        buffer
+            .tag("a", &[("href", r#""><script>alert(1)</script>"#)])
+            .buffer
+            .write_text("<b>");
+
+        assert_eq!(
+            buffer.buffer,
+            r#"<a href="&quot;&gt;&lt;script&gt;alert(1)&lt;/script&gt;">&lt;b&gt;</a>"#
+        );
+    }
+
+    #[test]
+    fn void_tag_escapes_attribute_values() {
+        let mut buffer = ToHtmlBuffer::new(Options::default(), false);
+        buffer.void_tag(
+            "img",
+            &[("src", "a.png"), ("alt", r#"x" onerror="alert(1)"#)],
+        );
+
+        assert_eq!(
+            buffer.buffer,
+            r#"<img src="a.png" alt="x&quot; onerror=&quot;alert(1)" />"#
+        );
+    }
+}
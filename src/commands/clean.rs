@@ -1,21 +1,147 @@
-use anyhow::{Context, Error};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Error};
+use clap::{Parser, ValueHint};
 
 use crate::models::Config;
-use std::path::PathBuf;
-
-pub fn execute(dir: PathBuf, dest_dir: Option<PathBuf>) -> Result<(), Error> {
-    let config = Config::from_disk("./book.toml")?;
-    let dir_to_remove = match dest_dir {
-        Some(dest_dir) => dest_dir.into(),
-        None => match config.build.as_ref().map(|b| b.build_dir.clone()) {
-            Some(build_dir) => config.book.src.join(&build_dir),
-            None => config.book.src.join(&dir),
-        },
-    };
-
-    if dir_to_remove.exists() {
-        std::fs::remove_dir_all(&dir_to_remove)
-            .with_context(|| "Unable to remove the build directory")?;
+
+#[derive(Clone, Debug, Parser)]
+pub struct CommandClean {
+    /// Lists the files that would be removed, without removing them
+    #[clap(long)]
+    dry_run: bool,
+    /// Output directory for the book\n\
+    /// Relative paths are interpreted relative to the book's root directory.\n\
+    /// If omitted, mdBook uses build.build-dir from book.toml \
+    /// or defaults to `./book`.
+    #[clap(long, short, value_hint = ValueHint::DirPath)]
+    dest_dir: Option<PathBuf>,
+    /// Root directory for the book
+    #[clap(value_hint = ValueHint::DirPath)]
+    dir: Option<PathBuf>,
+}
+
+pub fn execute(
+    CommandClean {
+        dry_run,
+        dest_dir,
+        dir,
+    }: &CommandClean,
+) -> Result<(), Error> {
+    let dir = dir
+        .as_ref()
+        .unwrap_or(&PathBuf::from_str(".").unwrap())
+        .canonicalize()
+        .map_err(|err| anyhow!("Cannot find the book.\n  Cause: {err}"))?;
+
+    let config = Config::from_disk(dir.join("book.toml"))?;
+    let build_dir = config.build_dir(&dir, dest_dir.as_deref());
+
+    if !build_dir.exists() {
+        println!("Nothing to clean");
+        return Ok(());
+    }
+
+    // The build directory is resolved from the configuration, so a mistake
+    // could point to the sources
+    let build_dir = build_dir.canonicalize()?;
+    let src_dir = dir.join(&config.book.src);
+
+    if build_dir == dir || !build_dir.starts_with(&dir) || src_dir.starts_with(&build_dir) {
+        return Err(anyhow!(
+            "Refusing to remove {build_dir:?}, which is not an output directory inside the book"
+        ));
+    }
+
+    if *dry_run {
+        println!("Would remove:");
+        list_files(&build_dir, &dir)?;
+        return Ok(());
     }
+
+    std::fs::remove_dir_all(&build_dir)
+        .map_err(|err| anyhow!("Cannot remove {build_dir:?}.\n  Cause: {err}"))?;
+
+    println!("Removed {}", build_dir.display());
+
+    Ok(())
+}
+
+/// Prints the files of `dir` relative to the book `root`
+fn list_files(dir: &Path, root: &Path) -> Result<(), Error> {
+    println!("  {}", dir.strip_prefix(root)?.display());
+
+    let mut entries = std::fs::read_dir(dir)
+        .map_err(|err| anyhow!("Cannot read {dir:?}.\n  Cause: {err}"))?
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.path());
+
+    for entry in entries {
+        if entry.file_type()?.is_dir() {
+            list_files(&entry.path(), root)?;
+        } else {
+            println!("  {}", entry.path().strip_prefix(root)?.display());
+        }
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// Creates a book with a built page
+    fn book(config: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+
+        fs::write(dir.path().join("book.toml"), config).unwrap();
+        fs::create_dir_all(dir.path().join("src/book")).unwrap();
+        fs::create_dir_all(dir.path().join("out")).unwrap();
+        fs::write(dir.path().join("out/index.html"), "").unwrap();
+
+        dir
+    }
+
+    fn clean(args: &[&str]) -> Result<(), Error> {
+        execute(&CommandClean::parse_from(["clean"].iter().chain(args)))
+    }
+
+    #[test]
+    fn build_dir_is_resolved_from_the_root() {
+        let dir = book("[build]\nbuild-dir = \"out\"\n");
+
+        clean(&[dir.path().to_str().unwrap()]).unwrap();
+
+        assert!(!dir.path().join("out").exists());
+        assert!(dir.path().join("src/book").exists());
+    }
+
+    #[test]
+    fn dry_run_removes_nothing() {
+        let dir = book("");
+
+        clean(&["--dry-run", "--dest-dir=out", dir.path().to_str().unwrap()]).unwrap();
+
+        assert!(dir.path().join("out/index.html").exists());
+    }
+
+    #[test]
+    fn only_output_directories_are_removed() {
+        let dir = book("");
+        let root = dir.path().to_str().unwrap();
+
+        for dest_dir in [".", "..", "src", "/tmp"] {
+            assert!(
+                clean(&["--dest-dir", dest_dir, root]).is_err(),
+                "{dest_dir}"
+            );
+        }
+
+        assert!(dir.path().join("book.toml").exists());
+        assert!(dir.path().join("src").exists());
+    }
+}
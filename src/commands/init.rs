@@ -1,59 +1,198 @@
+mod template;
+use template::{create_from_template, Placeholders};
+
 use std::collections::HashMap;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
+use clap::{Parser, ValueHint};
 
 use crate::models::config_book::BookConfig;
 use crate::models::lang_config::{Language, LanguageConfig};
 use crate::models::Config;
 
-use super::Commands;
+/// Environment variable with the template used when `--template` is not set
+const TEMPLATE_REPOSITORY: &str = "TEMPLATE_REPOSITORY";
+
+#[derive(Clone, Debug, Parser)]
+pub struct CommandInit {
+    /// Copies the default theme into your source folder
+    #[clap(long, short)]
+    theme: Option<String>,
+    /// Sets the book title
+    #[clap(long)]
+    title: Option<String>,
+    /// Sets the book description
+    #[clap(long)]
+    description: Option<String>,
+    /// A comma-separated list of the authors of the book
+    #[clap(long, value_delimiter = ',')]
+    authors: Vec<String>,
+    /// Sets the default language of the book
+    #[clap(long)]
+    language: Option<String>,
+    /// A comma-separated list of other languages of the book
+    #[clap(long, value_delimiter = ',')]
+    languages: Vec<String>,
+    /// Uses the default values instead of asking for the missing ones
+    ///
+    /// The values are never asked when the input is not a terminal.
+    #[clap(long, short)]
+    yes: bool,
+    /// Creates the book even if the directory already contains one
+    #[clap(long)]
+    force: bool,
+    /// Git URL or local path of a repository to create the book from
+    ///
+    /// If omitted, the TEMPLATE_REPOSITORY environment variable is used or a
+    /// builtin book is created.
+    #[clap(long, value_hint = ValueHint::AnyPath)]
+    template: Option<String>,
+    /// Directory to create the book in
+    #[clap(value_hint = ValueHint::AnyPath)]
+    dir: PathBuf,
+}
+
+pub fn execute(command: &CommandInit) -> Result<()> {
+    let env_template = std::env::var(TEMPLATE_REPOSITORY).ok();
+
+    init(command, env_template.as_deref())
+}
 
-pub fn execute(_theme: Option<String>, title: Option<String>, root_path: &PathBuf) -> Result<()> {
+/// Creates the book, from the template of `env_template` if `--template` is
+/// not set
+fn init(
+    CommandInit {
+        theme: _,
+        title,
+        description,
+        authors,
+        language,
+        languages,
+        yes,
+        force,
+        template,
+        dir: root_path,
+    }: &CommandInit,
+    env_template: Option<&str>,
+) -> Result<()> {
     log::debug!("Root Path: {root_path:?}");
-    let title = title.clone().unwrap_or(get_text(
-        "What is the title for this book?",
-        "My awesome book",
-    )?);
-    let description = get_text(
+
+    if root_path.join("book.toml").exists() && !force {
+        return Err(anyhow!(
+            "There is already a book at {root_path:?}.\n  Use --force to replace it"
+        ));
+    }
+
+    // Scripts and CI cannot answer the prompts
+    let interactive = !yes && std::io::stdin().is_terminal();
+    let ask = |value: &Option<String>, message: &str, default: &str| match value {
+        Some(value) => Ok(value.clone()),
+        None if interactive => get_text(message, default),
+        None => Ok(default.to_string()),
+    };
+
+    let title = ask(title, "What is the title for this book?", "My awesome book")?;
+    let description = ask(
+        description,
         "What is the description of the book?",
         "My awesome description",
     )?;
-    let lang = get_text("What is the default language of this book?", "en")?;
+    let lang = ask(language, "What is the default language of this book?", "en")?;
+
+    let mut langs = vec![lang.clone()];
+    for language in languages {
+        if !langs.contains(language) {
+            langs.push(language.clone());
+        }
+    }
+
+    // A requested template must be used, but the one of the environment falls
+    // back to the builtin book, for example when offline
+    let template = match template {
+        Some(template) => Some((template.clone(), true)),
+        None => env_template
+            .filter(|template| !template.is_empty())
+            .map(|template| (template.to_string(), false)),
+    };
+
+    if let Some((template, required)) = template {
+        // A template is only made multilingual when it is asked for
+        let template_langs = match languages.is_empty() {
+            true => &[][..],
+            false => &langs[..],
+        };
+        let placeholders = Placeholders {
+            title: &title,
+            description: &description,
+            language: &lang,
+            authors,
+            languages: template_langs,
+        };
+
+        match create_from_template(&template, root_path, &placeholders) {
+            Ok(()) => {
+                for lang in template_langs {
+                    if !root_path.join("src").join(lang).exists() {
+                        create_language_dir(root_path, lang, &title, &description)?;
+                    }
+                }
+
+                return Ok(());
+            }
+            Err(err) if required => return Err(err),
+            Err(err) => log::warn!("{err}\nCreating the builtin book instead"),
+        }
+    }
 
     let config = Config {
         book: BookConfig {
             title: Some(title.clone()),
-            authors: vec![],
+            authors: authors.clone(),
             description: Some(description.clone()),
             src: "src".into(),
             language: Some(lang.clone()),
             text_direction: None,
         },
-        language: Some(LanguageConfig(HashMap::from_iter([(
-            lang.clone(),
-            Language {
-                name: lang.clone(),
-                title: Some(title.clone()),
-                authors: None,
-                description: Some(description.clone()),
+        language: Some(LanguageConfig(HashMap::from_iter(langs.iter().map(
+            |lang| {
+                let language = Language {
+                    name: lang.clone(),
+                    title: Some(title.clone()),
+                    authors: None,
+                    description: Some(description.clone()),
+                };
+
+                (lang.clone(), language)
             },
-        )]))),
+        )))),
         ..Default::default()
     };
     log::trace!("Config generated: {config:?}");
 
+    std::fs::create_dir_all(root_path)?;
+    std::fs::File::create(root_path.join("book.toml"))?
+        .write_all(toml::to_string_pretty(&config).unwrap().as_bytes())?;
+
     // Generate struct src folder
+    for lang in langs {
+        create_language_dir(root_path, &lang, &title, &description)?;
+    }
+
+    Ok(())
+}
+
+/// Creates the source directory of a language, with its summary
+fn create_language_dir(root_path: &Path, lang: &str, title: &str, description: &str) -> Result<()> {
     let lang_path = root_path.join("src").join(lang);
     log::trace!("Book Path with Lang: {lang_path:?}");
     std::fs::create_dir_all(&lang_path)?;
-    std::fs::File::create(root_path.join("book.toml"))?
-        .write_all(toml::to_string_pretty(&config).unwrap().as_bytes())?;
     std::fs::File::create(lang_path.join("SUMMARY.md"))?
         .write_all(format!("og_title: {title}\nog_description: {description}\n---",).as_bytes())?;
     std::fs::File::create(lang_path.join("Readme.md"))?
         .write_all(b"# This is a index Example\nWith description")?;
+
     Ok(())
 }
 
@@ -72,3 +211,73 @@ fn get_text(s: &str, d: &str) -> Result<String> {
         .prompt()
         .context("Cannot get value from prompt")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs init without the template of the environment, which is only read
+    /// by `execute`
+    fn init(args: &[&str]) -> Result<()> {
+        let command = CommandInit::parse_from(["init"].iter().chain(args));
+
+        super::init(&command, None)
+    }
+
+    #[test]
+    fn book_is_created_without_prompts() {
+        let dir = tempfile::tempdir().unwrap();
+        let book = dir.path().join("book");
+
+        init(&[
+            "--yes",
+            "--title=Libro",
+            "--authors=Ana,Luis",
+            "--language=es",
+            "--languages=en,es",
+            book.to_str().unwrap(),
+        ])
+        .unwrap();
+
+        let config = Config::from_disk(book.join("book.toml")).unwrap();
+        let languages = config.language.unwrap().0;
+
+        assert_eq!(config.book.title.as_deref(), Some("Libro"));
+        assert_eq!(
+            config.book.description.as_deref(),
+            Some("My awesome description")
+        );
+        assert_eq!(config.book.authors, ["Ana", "Luis"]);
+        assert_eq!(config.book.language.as_deref(), Some("es"));
+        assert_eq!(languages.len(), 2);
+        assert!(book.join("src/es/SUMMARY.md").is_file());
+        assert!(book.join("src/en/SUMMARY.md").is_file());
+    }
+
+    #[test]
+    fn existing_book_is_kept_without_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let book = dir.path().to_str().unwrap();
+        std::fs::write(dir.path().join("book.toml"), "[book]\ntitle = \"Old\"\n").unwrap();
+
+        assert!(init(&["--yes", book]).is_err());
+
+        init(&["--yes", "--force", "--title=New", book]).unwrap();
+
+        let config = Config::from_disk(dir.path().join("book.toml")).unwrap();
+        assert_eq!(config.book.title.as_deref(), Some("New"));
+    }
+
+    #[test]
+    fn broken_template_of_the_environment_falls_back_to_the_builtin_book() {
+        let dir = tempfile::tempdir().unwrap();
+        let book = dir.path().join("book");
+        let missing = dir.path().join("missing");
+        let command = CommandInit::parse_from(["init", "--yes", book.to_str().unwrap()]);
+
+        super::init(&command, missing.to_str()).unwrap();
+
+        assert!(book.join("book.toml").is_file());
+        assert!(book.join("src/en/SUMMARY.md").is_file());
+    }
+}
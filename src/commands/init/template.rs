@@ -0,0 +1,300 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use git2::Repository;
+use toml_edit::visit_mut::VisitMut;
+use toml_edit::{Document, Formatted, Item, Table};
+
+/// Values of the new book, which replace the placeholders of a template, like
+/// `{{title}}`, and are set in its configuration
+pub struct Placeholders<'a> {
+    pub title: &'a str,
+    pub description: &'a str,
+    pub language: &'a str,
+    /// Replace the authors of the template, if any
+    pub authors: &'a [String],
+    /// Languages added to the configuration, if they are missing
+    pub languages: &'a [String],
+}
+
+impl Placeholders<'_> {
+    fn fill(&self, content: &str) -> String {
+        content
+            .replace("{{title}}", self.title)
+            .replace("{{description}}", self.description)
+            .replace("{{language}}", self.language)
+    }
+
+    /// Fills the placeholders of the strings of a configuration, so the
+    /// values are escaped like any TOML string, and sets the values of the
+    /// book that have no placeholder
+    fn fill_config(&self, content: &str) -> Result<String, toml_edit::TomlError> {
+        let mut config = content.parse::<Document>()?;
+
+        FillStrings(self).visit_document_mut(&mut config);
+
+        if !self.authors.is_empty() {
+            config["book"]["authors"] =
+                toml_edit::value(self.authors.iter().collect::<toml_edit::Array>());
+        }
+
+        if !self.languages.is_empty() {
+            // The languages are `[language.<lang>]` tables, without a header
+            // for `[language]`
+            let languages = config.entry("language").or_insert_with(|| {
+                let mut languages = Table::new();
+                languages.set_implicit(true);
+                Item::Table(languages)
+            });
+
+            if let Some(languages) = languages.as_table_mut() {
+                for language in self.languages {
+                    if languages.contains_key(language) {
+                        continue;
+                    }
+
+                    let mut table = Table::new();
+                    table["name"] = toml_edit::value(language);
+                    table["title"] = toml_edit::value(self.title);
+                    table["description"] = toml_edit::value(self.description);
+
+                    languages.insert(language, Item::Table(table));
+                }
+            }
+        }
+
+        Ok(config.to_string())
+    }
+}
+
+/// Fills the placeholders of every string of a configuration
+struct FillStrings<'a, 'b>(&'a Placeholders<'b>);
+
+impl VisitMut for FillStrings<'_, '_> {
+    fn visit_string_mut(&mut self, node: &mut Formatted<String>) {
+        let decor = node.decor().clone();
+
+        *node = Formatted::new(self.0.fill(node.value()));
+        *node.decor_mut() = decor;
+    }
+}
+
+/// Creates a book at `root_path` from the repository at `source`, a git URL or
+/// a local path.
+///
+/// The history of the template is not kept
+pub fn create_from_template(
+    source: &str,
+    root_path: &Path,
+    placeholders: &Placeholders,
+) -> Result<()> {
+    let temp_dir = tempfile::tempdir()
+        .map_err(|err| anyhow!("Cannot create a temporary directory.\n  Cause: {err}"))?;
+
+    log::info!("Cloning the template {source}...");
+
+    Repository::clone(source, temp_dir.path())
+        .map_err(|err| anyhow!("Cannot clone the template {source:?}.\n  Cause: {err}"))?;
+
+    copy_template(temp_dir.path(), root_path, placeholders)
+}
+
+/// Copies the files of a template, filling the placeholders of the
+/// configuration and the chapters
+fn copy_template(from: &Path, to: &Path, placeholders: &Placeholders) -> Result<()> {
+    fs::create_dir_all(to).map_err(|err| anyhow!("Cannot create {to:?}.\n  Cause: {err}"))?;
+
+    let entries =
+        fs::read_dir(from).map_err(|err| anyhow!("Cannot read {from:?}.\n  Cause: {err}"))?;
+
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        let target = to.join(entry.file_name());
+
+        if entry.file_name() == ".git" {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            copy_template(&path, &target, placeholders)?;
+            continue;
+        }
+
+        // Themes are handlebars templates too, so only the book is filled
+        let is_config = path.file_name().is_some_and(|name| name == "book.toml");
+        let is_chapter = matches!(
+            path.extension().and_then(|ext| ext.to_str()),
+            Some("md" | "mdx")
+        );
+
+        let result = match (is_config, is_chapter) {
+            (true, _) => {
+                let content = fs::read_to_string(&path)
+                    .map_err(|err| anyhow!("Cannot read {path:?}.\n  Cause: {err}"))?;
+                let config = placeholders
+                    .fill_config(&content)
+                    .map_err(|err| anyhow!("Cannot parse {path:?}.\n  Cause: {err}"))?;
+
+                fs::write(&target, config)
+            }
+            (false, true) => fs::read_to_string(&path)
+                .and_then(|content| fs::write(&target, placeholders.fill(&content))),
+            (false, false) => fs::copy(&path, &target).map(|_| ()),
+        };
+
+        result.map_err(|err| anyhow!("Cannot copy {path:?}.\n  Cause: {err}"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a repository with a commit of `files`
+    fn template_repository(dir: &Path, files: &[(&str, &str)]) {
+        let repository = Repository::init(dir).unwrap();
+
+        for (path, content) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        let mut index = repository.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        let tree = repository.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+
+        repository
+            .commit(Some("HEAD"), &signature, &signature, "Template", &tree, &[])
+            .unwrap();
+    }
+
+    #[test]
+    fn template_is_cloned_from_a_local_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("template");
+        let book = dir.path().join("book");
+
+        template_repository(
+            &template,
+            &[
+                ("book.toml", "[book]\ntitle = \"{{title}}\"\n"),
+                ("src/SUMMARY.md", "- [{{description}}](intro.md)\n"),
+                ("src/intro.md", "# {{title}} ({{language}})\n"),
+                ("theme/index.hbs", "<title>{{title}}</title>\n"),
+            ],
+        );
+
+        let placeholders = Placeholders {
+            title: "Libro",
+            description: "Un libro",
+            language: "es",
+            authors: &[],
+            languages: &[],
+        };
+
+        create_from_template(template.to_str().unwrap(), &book, &placeholders).unwrap();
+
+        let read = |path| fs::read_to_string(book.join(path)).unwrap();
+
+        assert_eq!(read("book.toml"), "[book]\ntitle = \"Libro\"\n");
+        assert_eq!(read("src/SUMMARY.md"), "- [Un libro](intro.md)\n");
+        assert_eq!(read("src/intro.md"), "# Libro (es)\n");
+        assert_eq!(read("theme/index.hbs"), "<title>{{title}}</title>\n");
+        assert!(!book.join(".git").exists());
+    }
+
+    #[test]
+    fn template_is_cloned_from_a_bare_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("template");
+        let bare = dir.path().join("template.git");
+        let book = dir.path().join("book");
+
+        template_repository(&template, &[("src/intro.md", "# {{title}}\n")]);
+        git2::build::RepoBuilder::new()
+            .bare(true)
+            .clone(template.to_str().unwrap(), &bare)
+            .unwrap();
+
+        let placeholders = Placeholders {
+            title: "Book",
+            description: "Book",
+            language: "en",
+            authors: &[],
+            languages: &[],
+        };
+
+        create_from_template(bare.to_str().unwrap(), &book, &placeholders).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(book.join("src/intro.md")).unwrap(),
+            "# Book\n"
+        );
+    }
+
+    #[test]
+    fn missing_template_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let placeholders = Placeholders {
+            title: "Book",
+            description: "Book",
+            language: "en",
+            authors: &[],
+            languages: &[],
+        };
+
+        let result = create_from_template(
+            dir.path().join("missing").to_str().unwrap(),
+            &dir.path().join("book"),
+            &placeholders,
+        );
+
+        assert!(result.is_err());
+        assert!(!dir.path().join("book").exists());
+    }
+
+    #[test]
+    fn configuration_values_are_escaped() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = dir.path().join("template");
+        let book = dir.path().join("book");
+
+        template_repository(
+            &template,
+            &[(
+                "book.toml",
+                "[book]\n# Filled by init\ntitle = \"{{title}}\"\nauthors = [\"Template\"]\n",
+            )],
+        );
+
+        let title = r#"Say "hi" \ bye"
+injected = true"#;
+        let placeholders = Placeholders {
+            title,
+            description: "Book",
+            language: "es",
+            authors: &["Ana".to_string()],
+            languages: &["es".to_string(), "en".to_string()],
+        };
+
+        create_from_template(template.to_str().unwrap(), &book, &placeholders).unwrap();
+
+        let content = fs::read_to_string(book.join("book.toml")).unwrap();
+        let config = content.parse::<toml::Table>().unwrap();
+
+        assert!(content.contains("# Filled by init"));
+        assert_eq!(config["book"]["title"].as_str(), Some(title));
+        assert_eq!(config["book"].get("injected"), None);
+        assert_eq!(config["book"]["authors"], toml::Value::from(vec!["Ana"]));
+        assert_eq!(config["language"]["en"]["name"].as_str(), Some("en"));
+        assert_eq!(config["language"]["es"]["title"].as_str(), Some(title));
+    }
+}
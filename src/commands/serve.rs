@@ -1,72 +1,140 @@
-use anyhow::Error;
-use axum::{
-    extract::{Extension, Path},
-    http::StatusCode,
-    response::Html,
-    routing::get,
-    Router,
-};
-use std::path::Path as StdPath;
-use std::{path::PathBuf, sync::Arc};
+mod livereload;
+use livereload::{BuildStatus, LIVERELOAD_PATH};
+mod static_files;
+
+use anyhow::{anyhow, Error};
+use axum::{extract::Extension, routing::get, Router};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::watch;
+
+use crate::models::Config;
+
+use super::build::Book;
+use super::watch::{wait_for_book, watch};
 
 pub struct ServeConfig {
     pub hostname: Option<String>,
     pub port: Option<u16>,
     pub open: bool,
     pub dest_dir: Option<PathBuf>,
+    pub dir: PathBuf,
+    pub allow_symlinks: bool,
 }
 
-pub async fn get_html(
-    Extension(config): Extension<Arc<ServeConfig>>,
-    Path((folder, file)): Path<(String, String)>,
-) -> (StatusCode, Html<String>) {
-    let dest_dir = config
-        .dest_dir
-        .as_deref()
-        .unwrap_or(StdPath::new("book/"))
-        .to_str()
-        .unwrap_or("book/");
-    let route = format!("{}/{}/{}", dest_dir, folder, file);
-    let path = StdPath::new(route.as_str());
-
-    if path.exists() {
-        let file = std::fs::read_to_string(path).unwrap();
-
-        (StatusCode::OK, Html(file))
-    } else {
-        (StatusCode::NOT_FOUND, Html(String::from("not-found")))
-    }
+/// State shared by the handlers of the server
+pub struct ServeState {
+    pub build_dir: PathBuf,
+    /// Path where the book is published, with leading and trailing slashes
+    pub site_path: String,
+    /// Serves symlinks that point outside of the build directory
+    pub allow_symlinks: bool,
 }
 
 pub async fn execute(config: ServeConfig) -> Result<(), Error> {
-    let port = config.port.clone().unwrap();
+    let port = config.port.unwrap();
     let hostname = config.hostname.clone().unwrap();
     let addr = format!("{}:{}", hostname, port);
 
-    let shared_config = Arc::new(config);
+    // The book is found from its directory, not the working directory
+    let dir = config
+        .dir
+        .canonicalize()
+        .map_err(|err| anyhow!("Cannot find the book at {:?}.\n  Cause: {err}", config.dir))?;
+    let dest_dir = config.dest_dir.as_deref();
+
+    // The server starts even if the book is broken, so it can be fixed while
+    // the pages show the error
+    let (status_sender, status) = watch::channel(BuildStatus::default());
+    let report_error = |message: &str, err: &Error| {
+        eprintln!("{message}.\n  Cause: {err:#}");
+        status_sender.send_replace(BuildStatus {
+            build: 0,
+            error: Some(format!("{err:#}")),
+        });
+    };
+
+    let book = match Book::load(&dir, dest_dir).await {
+        Ok(book) => {
+            match book.build().await {
+                Ok(()) => println!("Book built at {}", book.build_dir.display()),
+                Err(err) => report_error("Build failed", &err),
+            }
+            Some(book)
+        }
+        Err(err) => {
+            report_error("Cannot load the book", &err);
+            None
+        }
+    };
+
+    let (build_dir, site_path) = match &book {
+        Some(book) => (book.build_dir.clone(), book.config.site_path()),
+        None => fallback_paths(&dir, dest_dir)?,
+    };
+    let state = Arc::new(ServeState {
+        build_dir: build_dir.clone(),
+        site_path: site_path.clone(),
+        allow_symlinks: config.allow_symlinks,
+    });
 
     let app = Router::new()
-        .route("/:folder/:file", get(get_html))
-        .layer(Extension(shared_config.clone()));
+        .route(LIVERELOAD_PATH, get(livereload::events))
+        .fallback(get(static_files::serve_file))
+        .layer(Extension(state))
+        .layer(Extension(status));
 
     println!("Server running on port {}", port.clone());
 
-    let server_task = tokio::spawn(async move {
-        let listener = tokio::net::TcpListener::bind(addr.clone()).await.unwrap();
-        axum::serve(listener, app).await.unwrap();
-    });
+    let server = async move {
+        let listener = tokio::net::TcpListener::bind(addr.clone()).await?;
+        axum::serve(listener, app).await?;
 
-    let browser_task = tokio::spawn(async move {
-        let url = format!("http://{}:{}", hostname, port);
+        Ok::<_, Error>(())
+    };
 
-        if shared_config.open && webbrowser::open(url.as_str()).is_ok() {
-            println!("Browser is open");
-        } else {
-            println!("Browser is not open");
-        }
-    });
+    let mut on_build = |result: Result<(), &Error>| {
+        status_sender.send_modify(|status| {
+            status.build += 1;
+            status.error = result.err().map(|err| format!("{err:#}"));
+        });
+    };
+
+    let watcher = async {
+        let book = match book {
+            Some(book) => book,
+            None => wait_for_book(&dir, dest_dir, &build_dir, &mut on_build).await?,
+        };
 
-    tokio::join!(server_task, browser_task);
+        watch(book, dest_dir, on_build).await
+    };
+
+    // The browser cannot open the unspecified address
+    let browser_host = match hostname.as_str() {
+        "0.0.0.0" | "::" => "localhost",
+        hostname => hostname,
+    };
+    let url = format!("http://{}:{}{}", browser_host, port, site_path);
+
+    if config.open && webbrowser::open(url.as_str()).is_ok() {
+        println!("Browser is open");
+    } else {
+        println!("Browser is not open");
+    }
+
+    tokio::try_join!(server, watcher)?;
 
     Ok(())
 }
+
+/// Build directory and site path of a book that cannot be loaded, from its
+/// configuration if it can be read
+fn fallback_paths(dir: &Path, dest_dir: Option<&Path>) -> Result<(PathBuf, String), Error> {
+    let config = Config::from_disk(dir.join("book.toml")).unwrap_or_default();
+    let build_dir = config.build_dir(dir, dest_dir);
+
+    std::fs::create_dir_all(&build_dir)
+        .and_then(|()| build_dir.canonicalize())
+        .map(|build_dir| (build_dir, config.site_path()))
+        .map_err(|err| anyhow!("Cannot create {build_dir:?}.\n  Cause: {err}"))
+}
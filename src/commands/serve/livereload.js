@@ -0,0 +1,39 @@
+"use strict";
+
+(function liveReload() {
+    var source = new EventSource("/__livereload");
+
+    source.addEventListener("reload", function () {
+        window.location.reload();
+    });
+
+    // Shows why the book could not be built instead of the outdated page
+    source.addEventListener("build-error", function (event) {
+        var overlay = document.getElementById("livereload-overlay");
+
+        if (!overlay) {
+            overlay = document.createElement("div");
+            overlay.id = "livereload-overlay";
+            overlay.setAttribute("role", "alert");
+            overlay.style.cssText = [
+                "position: fixed",
+                "inset: 0",
+                "z-index: 1000",
+                "overflow: auto",
+                "padding: 2em",
+                "color: #ffdede",
+                "background-color: rgba(20, 0, 0, 0.9)",
+                "font-family: monospace",
+            ].join(";");
+
+            var title = document.createElement("h2");
+            title.textContent = "The book could not be built";
+            overlay.appendChild(title);
+            overlay.appendChild(document.createElement("pre"));
+
+            document.body.appendChild(overlay);
+        }
+
+        overlay.querySelector("pre").textContent = event.data;
+    });
+})();
@@ -0,0 +1,164 @@
+use std::convert::Infallible;
+
+use axum::extract::Extension;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use tokio::sync::watch;
+use tokio_stream::wrappers::WatchStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Path of the endpoint that notifies the pages when the book is built
+pub const LIVERELOAD_PATH: &str = "/__livereload";
+
+const CLIENT: &str = include_str!("livereload.js");
+
+/// Result of the last build of the book
+#[derive(Clone, Debug, Default)]
+pub struct BuildStatus {
+    /// Number of builds since the server started
+    pub build: u64,
+    /// Error of the last build, if it failed
+    pub error: Option<String>,
+}
+
+/// Streams the builds of the book as Server-Sent Events, so the pages can
+/// reload themselves.
+///
+/// A page that connects while the book is broken receives the error too, as it
+/// was served outdated
+pub async fn events(
+    Extension(status): Extension<watch::Receiver<BuildStatus>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let current = {
+        let status = status.borrow();
+        status.error.is_some().then(|| build_event(&status))
+    };
+    let current = tokio_stream::iter(current);
+
+    let changes = WatchStream::from_changes(status).map(|status| build_event(&status));
+
+    Sse::new(current.chain(changes).map(Ok)).keep_alive(KeepAlive::default())
+}
+
+fn build_event(status: &BuildStatus) -> Event {
+    match &status.error {
+        // Events cannot contain carriage returns, which errors can quote from
+        // files with CRLF line endings
+        Some(error) => Event::default()
+            .event("build-error")
+            .data(error.replace('\r', "")),
+        None => Event::default()
+            .event("reload")
+            .data(status.build.to_string()),
+    }
+}
+
+/// Adds the live reload client to an HTML page
+pub fn inject_client(html: &str) -> String {
+    let script = format!("<script>{CLIENT}</script>\n");
+
+    match html.rfind("</body>") {
+        Some(index) => format!("{}{script}{}", &html[..index], &html[index..]),
+        None => format!("{html}{script}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::body::BodyDataStream;
+    use axum::response::IntoResponse;
+
+    use super::*;
+
+    /// Connects to the events of a build status
+    async fn connect(status: watch::Receiver<BuildStatus>) -> BodyDataStream {
+        events(Extension(status))
+            .await
+            .into_response()
+            .into_body()
+            .into_data_stream()
+    }
+
+    /// Next event sent to a page, `None` if there is none for a while
+    async fn next_event(stream: &mut BodyDataStream) -> Option<String> {
+        let chunk = tokio::time::timeout(Duration::from_millis(100), stream.next()).await;
+
+        chunk
+            .ok()
+            .flatten()
+            .map(|chunk| String::from_utf8(chunk.unwrap().to_vec()).unwrap())
+    }
+
+    #[test]
+    fn client_is_injected_before_the_end_of_the_body() {
+        let html = inject_client("<html><body><p>Page</p></body></html>");
+
+        assert!(html.starts_with("<html><body><p>Page</p><script>"));
+        assert!(html.ends_with("</script>\n</body></html>"));
+        assert!(html.contains(LIVERELOAD_PATH));
+    }
+
+    #[test]
+    fn client_is_appended_without_body() {
+        let html = inject_client("<p>Fragment</p>");
+
+        assert!(html.starts_with("<p>Fragment</p><script>"));
+        assert!(html.ends_with("</script>\n"));
+    }
+
+    #[tokio::test]
+    async fn builds_are_sent_to_the_pages() {
+        let (sender, status) = watch::channel(BuildStatus::default());
+        let mut stream = connect(status).await;
+
+        assert_eq!(next_event(&mut stream).await, None);
+
+        sender.send_modify(|status| {
+            status.build = 1;
+            status.error = Some("Cannot read \"intro.md\"".to_string());
+        });
+        assert_eq!(
+            next_event(&mut stream).await.as_deref(),
+            Some("event: build-error\ndata: Cannot read \"intro.md\"\n\n")
+        );
+
+        sender.send_modify(|status| {
+            status.build = 2;
+            status.error = None;
+        });
+        assert_eq!(
+            next_event(&mut stream).await.as_deref(),
+            Some("event: reload\ndata: 2\n\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn pages_of_a_broken_book_receive_its_error() {
+        let (_sender, status) = watch::channel(BuildStatus {
+            build: 0,
+            error: Some("Invalid configuration file".to_string()),
+        });
+        let mut stream = connect(status).await;
+
+        assert_eq!(
+            next_event(&mut stream).await.as_deref(),
+            Some("event: build-error\ndata: Invalid configuration file\n\n")
+        );
+        assert_eq!(next_event(&mut stream).await, None);
+    }
+
+    #[tokio::test]
+    async fn carriage_returns_are_removed_from_errors() {
+        let (_sender, status) = watch::channel(BuildStatus {
+            build: 0,
+            error: Some("Invalid book.toml:\r\n  title = 1\r\n".to_string()),
+        });
+        let mut stream = connect(status).await;
+
+        assert_eq!(
+            next_event(&mut stream).await.as_deref(),
+            Some("event: build-error\ndata: Invalid book.toml:\ndata:   title = 1\ndata: \n\n")
+        );
+    }
+}
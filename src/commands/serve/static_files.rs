@@ -0,0 +1,407 @@
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use axum::extract::Extension;
+use axum::http::{header, StatusCode, Uri};
+use axum::response::{Html, IntoResponse, Redirect, Response};
+use percent_encoding::percent_decode_str;
+use tokio::fs;
+
+use super::livereload::inject_client;
+use super::ServeState;
+
+/// File of the build directory that answers a request
+#[derive(Debug, PartialEq, Eq)]
+enum Resolved {
+    File(PathBuf),
+    /// Directories are redirected to their URL with a trailing slash, so the
+    /// relative links of their index page work
+    Redirect(String),
+    /// The path points outside of the build directory
+    Forbidden,
+    NotFound,
+}
+
+/// Serves the files of the build directory, like a static file server would,
+/// under the site path of the book
+pub async fn serve_file(Extension(state): Extension<Arc<ServeState>>, uri: Uri) -> Response {
+    // The site path without its trailing slash, empty for the root
+    let prefix = state.site_path.trim_end_matches('/');
+
+    let uri_path = match uri.path().strip_prefix(prefix) {
+        Some(uri_path) if uri_path.starts_with('/') => uri_path,
+        // Like a directory, the site path is redirected to its URL with a
+        // trailing slash
+        Some("") => return Redirect::permanent(&state.site_path).into_response(),
+        _ if uri.path() == "/" => return Redirect::temporary(&state.site_path).into_response(),
+        _ => return not_found(&state).await,
+    };
+
+    match resolve(&state.build_dir, uri_path, state.allow_symlinks) {
+        Resolved::File(file) => match file_response(&file, StatusCode::OK).await {
+            Some(response) => response,
+            None => not_found(&state).await,
+        },
+        Resolved::Redirect(location) => {
+            let location = format!("{prefix}{location}");
+            let location = match uri.query() {
+                Some(query) => format!("{location}?{query}"),
+                None => location,
+            };
+
+            Redirect::permanent(&location).into_response()
+        }
+        Resolved::Forbidden => {
+            log::warn!("Request outside of the build directory: {}", uri.path());
+            (StatusCode::FORBIDDEN, "Forbidden").into_response()
+        }
+        Resolved::NotFound => not_found(&state).await,
+    }
+}
+
+/// Finds the file for the path of a URL, confined to `build_dir`, which must
+/// be canonical.
+///
+/// Directories are served by their `index.html` and pages can be requested
+/// without the `.html` extension. Symlinks that point outside of the build
+/// directory are only followed when `allow_symlinks` is set
+fn resolve(build_dir: &Path, uri_path: &str, allow_symlinks: bool) -> Resolved {
+    let Ok(url_path) = percent_decode_str(uri_path).decode_utf8() else {
+        return Resolved::NotFound;
+    };
+
+    // Encoded separators and dots are decoded, so they are checked here
+    let relative_path = Path::new(url_path.trim_start_matches('/'));
+    let is_relative = relative_path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+
+    if !is_relative || url_path.contains('\\') || url_path.contains('\0') {
+        return Resolved::Forbidden;
+    }
+
+    let path = build_dir.join(relative_path);
+
+    let resolved = if path.is_dir() {
+        let index = path.join("index.html");
+
+        match index.is_file() {
+            true if url_path.ends_with('/') => Resolved::File(index),
+            // The URL is kept encoded, and with a single leading slash to
+            // not redirect to another host
+            true => {
+                let uri_path = uri_path.trim_start_matches('/');
+                return Resolved::Redirect(format!("/{uri_path}/"));
+            }
+            false => Resolved::NotFound,
+        }
+    } else if path.is_file() {
+        Resolved::File(path)
+    } else if path.extension().is_none() && path.with_extension("html").is_file() {
+        Resolved::File(path.with_extension("html"))
+    } else {
+        Resolved::NotFound
+    };
+
+    match resolved {
+        Resolved::File(file) => confine(build_dir, file, allow_symlinks),
+        resolved => resolved,
+    }
+}
+
+/// Checks that the real path of a file, after following the symlinks, is
+/// inside of the build directory
+fn confine(build_dir: &Path, file: PathBuf, allow_symlinks: bool) -> Resolved {
+    match file.canonicalize() {
+        Ok(real_path) if real_path.starts_with(build_dir) => Resolved::File(real_path),
+        Ok(_) if allow_symlinks => Resolved::File(file),
+        Ok(_) => Resolved::Forbidden,
+        Err(_) => Resolved::NotFound,
+    }
+}
+
+async fn file_response(file: &Path, status: StatusCode) -> Option<Response> {
+    let content = match fs::read(file).await {
+        Ok(content) => content,
+        Err(err) => {
+            log::warn!("Cannot read {file:?}.\n  Cause: {err}");
+            return None;
+        }
+    };
+
+    let mime = mime_guess::from_path(file).first_or_octet_stream();
+
+    let content = if mime == mime_guess::mime::TEXT_HTML {
+        inject_client(&String::from_utf8_lossy(&content)).into_bytes()
+    } else {
+        content
+    };
+
+    let headers = [
+        (header::CONTENT_TYPE, mime.to_string()),
+        // Pages change on every build
+        (header::CACHE_CONTROL, "no-cache".to_string()),
+    ];
+
+    Some((status, headers, content).into_response())
+}
+
+/// Page served for missing files when the book has no 404 page, for example
+/// because it could not be built yet
+const NOT_FOUND_PAGE: &str = "<!DOCTYPE html>
+<html>
+<head><meta charset=\"utf-8\"><title>Not found</title></head>
+<body>
+<h1>Not found</h1>
+</body>
+</html>
+";
+
+/// Responds with the 404 page of the book, or a plain page with the live
+/// reload client if it was not built, which shows the errors of the build
+async fn not_found(state: &ServeState) -> Response {
+    let page = state.build_dir.join("404.html");
+
+    match file_response(&page, StatusCode::NOT_FOUND).await {
+        Some(response) => response,
+        None => (StatusCode::NOT_FOUND, Html(inject_client(NOT_FOUND_PAGE))).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    #[cfg(unix)]
+    use std::os::unix::fs::symlink;
+
+    use tempfile::TempDir;
+
+    use super::super::livereload::LIVERELOAD_PATH;
+    use super::*;
+
+    /// Creates a build directory next to a secret file
+    fn setup() -> (TempDir, PathBuf) {
+        let root = tempfile::tempdir().unwrap();
+        let build_dir = root.path().join("book");
+
+        fs::create_dir_all(build_dir.join("guide")).unwrap();
+        fs::write(build_dir.join("index.html"), "index").unwrap();
+        fs::write(build_dir.join("guide/index.html"), "guide").unwrap();
+        fs::write(build_dir.join("guide/página.html"), "page").unwrap();
+        fs::write(root.path().join("secret.txt"), "secret").unwrap();
+
+        #[cfg(unix)]
+        {
+            symlink(root.path().join("secret.txt"), build_dir.join("link.txt")).unwrap();
+            symlink(root.path(), build_dir.join("root")).unwrap();
+        }
+
+        let build_dir = build_dir.canonicalize().unwrap();
+
+        (root, build_dir)
+    }
+
+    async fn serve(build_dir: &Path, site_path: &str, uri: &str) -> Response {
+        let state = Arc::new(ServeState {
+            build_dir: build_dir.to_path_buf(),
+            site_path: site_path.to_string(),
+            allow_symlinks: false,
+        });
+
+        serve_file(Extension(state), uri.parse().unwrap()).await
+    }
+
+    async fn status(build_dir: &Path, uri: &str) -> StatusCode {
+        serve(build_dir, "/", uri).await.status()
+    }
+
+    fn location(response: &Response) -> &str {
+        response.headers()[header::LOCATION].to_str().unwrap()
+    }
+
+    #[test]
+    fn files_are_resolved() {
+        let (_root, build_dir) = setup();
+
+        let resolve = |path| resolve(&build_dir, path, false);
+
+        assert_eq!(resolve("/"), Resolved::File(build_dir.join("index.html")));
+        assert_eq!(
+            resolve("/guide/"),
+            Resolved::File(build_dir.join("guide/index.html"))
+        );
+        assert_eq!(resolve("/guide"), Resolved::Redirect("/guide/".to_string()));
+        assert_eq!(
+            resolve("//guide"),
+            Resolved::Redirect("/guide/".to_string())
+        );
+        assert_eq!(
+            resolve("/guide/p%C3%A1gina"),
+            Resolved::File(build_dir.join("guide/página.html"))
+        );
+        assert_eq!(resolve("/missing.html"), Resolved::NotFound);
+    }
+
+    #[test]
+    fn parent_components_are_forbidden() {
+        let (_root, build_dir) = setup();
+
+        for path in [
+            "/../secret.txt",
+            "/guide/../../secret.txt",
+            "/%2e%2e/secret.txt",
+            "/guide/..%2f..%2fsecret.txt",
+            "/%2e%2e%5csecret.txt",
+            "/..",
+        ] {
+            assert_eq!(
+                resolve(&build_dir, path, false),
+                Resolved::Forbidden,
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn absolute_paths_stay_in_the_build_dir() {
+        let (root, build_dir) = setup();
+        let secret = root.path().join("secret.txt");
+        let secret = secret.to_str().unwrap();
+
+        assert_eq!(
+            resolve(&build_dir, &format!("/{secret}"), false),
+            Resolved::NotFound
+        );
+        assert_eq!(
+            resolve(&build_dir, "/guide/%00", false),
+            Resolved::Forbidden
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_cannot_escape_unless_allowed() {
+        let (_root, build_dir) = setup();
+
+        assert_eq!(resolve(&build_dir, "/link.txt", false), Resolved::Forbidden);
+        assert_eq!(
+            resolve(&build_dir, "/root/secret.txt", false),
+            Resolved::Forbidden
+        );
+        assert_eq!(
+            resolve(&build_dir, "/link.txt", true),
+            Resolved::File(build_dir.join("link.txt"))
+        );
+    }
+
+    #[tokio::test]
+    async fn traversal_attempts_are_rejected() {
+        let (_root, build_dir) = setup();
+
+        assert_eq!(status(&build_dir, "/").await, StatusCode::OK);
+        assert_eq!(
+            status(&build_dir, "/../secret.txt").await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(&build_dir, "/%2e%2e/secret.txt").await,
+            StatusCode::FORBIDDEN
+        );
+        #[cfg(unix)]
+        assert_eq!(status(&build_dir, "/link.txt").await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            status(&build_dir, "/secret.txt").await,
+            StatusCode::NOT_FOUND
+        );
+    }
+
+    #[tokio::test]
+    async fn book_is_served_under_the_site_path() {
+        let (_root, build_dir) = setup();
+        let serve = |uri| serve(&build_dir, "/libro/", uri);
+
+        assert_eq!(serve("/libro/").await.status(), StatusCode::OK);
+        assert_eq!(serve("/libro/guide/").await.status(), StatusCode::OK);
+        assert_eq!(serve("/index.html").await.status(), StatusCode::NOT_FOUND);
+        assert_eq!(serve("/libros/").await.status(), StatusCode::NOT_FOUND);
+
+        assert_eq!(location(&serve("/").await), "/libro/");
+        assert_eq!(location(&serve("/libro").await), "/libro/");
+        assert_eq!(
+            location(&serve("/libro/guide?q=1").await),
+            "/libro/guide/?q=1"
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_404_page_shows_the_build_errors() {
+        let (_root, build_dir) = setup();
+        let response = serve(&build_dir, "/", "/missing").await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(body.contains("<h1>Not found</h1>"));
+        assert!(body.contains(LIVERELOAD_PATH));
+    }
+
+    async fn body(response: Response) -> Vec<u8> {
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn files_have_their_mime_type() {
+        let (_root, build_dir) = setup();
+        fs::write(build_dir.join("book.css"), "body {}").unwrap();
+        fs::write(build_dir.join("book.js"), "let a;").unwrap();
+        fs::write(build_dir.join("data.unknown"), "data").unwrap();
+
+        for (uri, mime) in [
+            ("/", "text/html"),
+            ("/guide/p%C3%A1gina", "text/html"),
+            ("/book.css", "text/css"),
+            ("/book.js", "text/javascript"),
+            ("/data.unknown", "application/octet-stream"),
+        ] {
+            let response = serve(&build_dir, "/", uri).await;
+            let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap();
+
+            assert!(content_type.starts_with(mime), "{uri}: {content_type}");
+        }
+    }
+
+    #[tokio::test]
+    async fn binary_files_are_served_unchanged() {
+        let (_root, build_dir) = setup();
+        // Not valid UTF-8, and with a `</body>` that must not get the client
+        let image = [&[0x89, b'P', b'N', b'G', 0xff, 0xfe, 0x00][..], b"</body>"].concat();
+        fs::write(build_dir.join("image.png"), &image).unwrap();
+
+        let response = serve(&build_dir, "/", "/image.png").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+        assert_eq!(body(response).await, image);
+    }
+
+    #[tokio::test]
+    async fn missing_files_get_the_404_page_of_the_book() {
+        let (_root, build_dir) = setup();
+        fs::write(build_dir.join("404.html"), "<body>Lost</body>").unwrap();
+
+        let response = serve(&build_dir, "/", "/guide/missing").await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/html");
+
+        let body = String::from_utf8(body(response).await).unwrap();
+        assert!(body.starts_with("<body>Lost<script>"));
+    }
+}
@@ -0,0 +1,392 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use clap::{Parser, ValueHint};
+use markdown::mdast as ast;
+use tokio::fs;
+use tokio::process::Command;
+
+use super::build::{
+    chapter_base, chapter_with_extension, read_chapter, split_hidden_lines, Book, CodeInfo,
+};
+
+#[derive(Clone, Debug, Parser)]
+pub struct CommandTest {
+    /// Accepted for compatibility, testing does not build a book to open
+    #[clap(long, short)]
+    open: bool,
+    /// Only tests the chapter with this title or path, relative to the source
+    /// directory
+    #[clap(long, short)]
+    chapter: Option<String>,
+    /// A comma-separated list of directories to add to the crate search path when building tests
+    #[clap(long, short, value_hint = ValueHint::FilePath, value_delimiter = ',')]
+    library_path: Vec<PathBuf>,
+    /// Root directory for the book
+    #[clap(value_hint = ValueHint::DirPath)]
+    dir: Option<PathBuf>,
+}
+
+pub async fn execute(
+    CommandTest {
+        open,
+        chapter,
+        library_path,
+        dir,
+    }: &CommandTest,
+) -> Result<()> {
+    let dir = dir
+        .as_ref()
+        .unwrap_or(&PathBuf::from_str(".").unwrap())
+        .canonicalize()?;
+
+    if *open {
+        log::warn!("The book is not built when testing it, there is nothing to open");
+    }
+
+    // Testing does not change the book, not even its missing chapters
+    let book = Book::open(&dir, None).await?;
+
+    let hidelines = book
+        .config
+        .html_config()
+        .code
+        .and_then(|code| code.hidelines)
+        .and_then(|hidelines| hidelines.get("rust").cloned());
+
+    let mut args = Vec::new();
+    if let Some(edition) = book.config.rust.edition {
+        args.extend(["--edition".to_string(), edition.as_str().to_string()]);
+    }
+    for path in library_path {
+        // rustdoc runs in the temporary directory
+        let path = std::path::absolute(path)
+            .map_err(|err| anyhow!("Cannot find {path:?}.\n  Cause: {err}"))?;
+        args.extend(["-L".to_string(), path.to_string_lossy().into_owned()]);
+    }
+
+    let temp_dir = tempfile::tempdir()
+        .map_err(|err| anyhow!("Cannot create a temporary directory.\n  Cause: {err}"))?;
+
+    let mut found_chapter = false;
+    let mut failed = Vec::new();
+
+    for (title, file) in book.chapters() {
+        let path = file.strip_prefix(&book.src_dir)?;
+
+        if let Some(chapter) = chapter {
+            let name = Path::new(chapter);
+
+            if title != chapter && path != name && chapter_base(path) != name {
+                continue;
+            }
+        }
+
+        found_chapter = true;
+
+        let Some(doctests) = doctests(&read_chapter(file).await?, hidelines.as_deref()) else {
+            continue;
+        };
+
+        // The input keeps the path of the chapter, which rustdoc shows in the
+        // name of the tests, but rustdoc only reads `.md` files
+        let relative_path = chapter_with_extension(file.strip_prefix(&book.dir)?, "md");
+        let input = temp_dir.path().join(&relative_path);
+
+        if let Some(parent) = input.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(&input, doctests)
+            .await
+            .map_err(|err| anyhow!("Cannot write {input:?}.\n  Cause: {err}"))?;
+
+        println!("Testing {}...", relative_path.display());
+
+        let status = Command::new("rustdoc")
+            .current_dir(temp_dir.path())
+            .arg(&relative_path)
+            .arg("--test")
+            .args(&args)
+            .status()
+            .await
+            .map_err(|err| anyhow!("Cannot run rustdoc.\n  Cause: {err}"))?;
+
+        if !status.success() {
+            failed.push(relative_path.display().to_string());
+        }
+    }
+
+    if let (Some(chapter), false) = (chapter, found_chapter) {
+        return Err(anyhow!("There is no chapter {chapter:?}"));
+    }
+
+    if !failed.is_empty() {
+        return Err(anyhow!("Tests failed in:\n  {}", failed.join("\n  ")));
+    }
+
+    Ok(())
+}
+
+/// Markdown with only the headings and Rust code blocks of a chapter, for
+/// rustdoc to test. `None` if the chapter has no Rust code.
+///
+/// Every heading and code block is kept at its line, so rustdoc reports the
+/// positions of the chapter
+fn doctests(node: &ast::Node, hidelines: Option<&str>) -> Option<String> {
+    let mut doctests = Doctests {
+        markdown: String::new(),
+        line: 1,
+        tests: 0,
+        hidelines,
+    };
+
+    doctests.visit(node);
+
+    (doctests.tests > 0).then_some(doctests.markdown)
+}
+
+struct Doctests<'a> {
+    markdown: String,
+    /// Line of the chapter where the next line is written
+    line: usize,
+    tests: usize,
+    /// Prefix of the hidden lines of Rust code from the configuration
+    hidelines: Option<&'a str>,
+}
+
+impl Doctests<'_> {
+    fn visit(&mut self, node: &ast::Node) {
+        match node {
+            ast::Node::Heading(heading) => {
+                self.move_to(node.position());
+                self.push_line(&format!(
+                    "{} {}",
+                    "#".repeat(heading.depth as usize),
+                    node.to_string().replace('\n', " ")
+                ));
+            }
+            ast::Node::Code(code) => self.code(code),
+            node => {
+                for child in node.children().into_iter().flatten() {
+                    self.visit(child);
+                }
+            }
+        }
+    }
+
+    fn code(&mut self, code: &ast::Code) {
+        let mut info = CodeInfo::parse(code.lang.as_deref(), code.meta.as_deref());
+
+        // Like rustdoc, code without a language or with only attributes of
+        // tests is Rust
+        match info.language.take() {
+            Some(language) if language == "rust" => {}
+            Some(language) => {
+                info.attributes.insert(0, language);
+
+                let is_rust = info.attributes.iter().all(|attribute| {
+                    is_test_attribute(attribute) || attribute.starts_with("hidelines=")
+                });
+
+                if !is_rust {
+                    return;
+                }
+            }
+            None => {}
+        }
+
+        // rustdoc knows the attributes of tests, like `ignore` or
+        // `edition2024` to override the edition of the book, but not the ones
+        // of the book
+        let attributes = info
+            .attributes
+            .iter()
+            .filter(|attribute| !attribute.starts_with("hidelines="))
+            .fold(String::from("rust"), |info, attribute| {
+                format!("{info},{attribute}")
+            });
+
+        // The fence is longer than any run of backticks in the code
+        let mut fence = "```".to_string();
+        while code.value.contains(&fence) {
+            fence.push('`');
+        }
+
+        self.move_to(code.position.as_ref());
+        self.push_line(&format!("{fence}{attributes}"));
+
+        let prefix = info.hidelines().or(self.hidelines);
+
+        for line in split_hidden_lines(&code.value, Some("rust"), prefix) {
+            // The whole code is compiled, so the lines that rustdoc would hide
+            // are escaped
+            let content = line.text.trim_start();
+            let indent = &line.text[..line.text.len() - content.len()];
+
+            match content.starts_with('#') {
+                true => self.push_line(&format!("{indent}#{content}")),
+                false => self.push_line(&line.text),
+            }
+        }
+
+        self.push_line(&fence);
+        self.tests += 1;
+    }
+
+    /// Adds empty lines until the start of a node
+    fn move_to(&mut self, position: Option<&markdown::unist::Position>) {
+        let Some(position) = position else {
+            return;
+        };
+
+        while self.line < position.start.line {
+            self.push_line("");
+        }
+    }
+
+    fn push_line(&mut self, line: &str) {
+        self.markdown.push_str(line);
+        self.markdown.push('\n');
+        self.line += 1;
+    }
+}
+
+/// Attributes of rustdoc tests, like `should_panic` or `edition2021`
+fn is_test_attribute(attribute: &str) -> bool {
+    let is_error_code = attribute
+        .strip_prefix('E')
+        .is_some_and(|code| code.len() == 4 && code.chars().all(|c| c.is_ascii_digit()));
+
+    matches!(
+        attribute,
+        "ignore" | "should_panic" | "no_run" | "compile_fail" | "test_harness" | "standalone_crate"
+    ) || attribute.starts_with("ignore-")
+        || attribute.starts_with("edition")
+        || is_error_code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Paths of the files and directories under `dir`
+    fn tree(dir: &Path) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+
+            if path.is_dir() {
+                paths.extend(tree(&path));
+            }
+            paths.push(path);
+        }
+
+        paths.sort();
+        paths
+    }
+
+    fn render(markdown: &str, hidelines: Option<&str>) -> Option<String> {
+        let node = markdown::to_mdast(markdown, &markdown::ParseOptions::gfm()).unwrap();
+
+        doctests(&node, hidelines)
+    }
+
+    #[test]
+    fn chapters_without_rust_are_skipped() {
+        assert_eq!(render("# Title\n\n```toml\na = 1\n```\n", None), None);
+    }
+
+    #[test]
+    fn code_without_language_is_rust() {
+        assert_eq!(
+            render("```\nfn main() {}\n```\n", None).as_deref(),
+            Some("```rust\nfn main() {}\n```\n")
+        );
+    }
+
+    #[test]
+    fn code_with_attributes_of_tests_is_rust() {
+        assert_eq!(
+            render("```should_panic\npanic!();\n```\n", None).as_deref(),
+            Some("```rust,should_panic\npanic!();\n```\n")
+        );
+        assert_eq!(
+            render(
+                "```compile_fail,E0308 edition2021\nlet a: u8 = \"\";\n```\n",
+                None
+            )
+            .as_deref(),
+            Some("```rust,compile_fail,E0308,edition2021\nlet a: u8 = \"\";\n```\n")
+        );
+        assert_eq!(render("```ignore,toml\na = 1\n```\n", None), None);
+    }
+
+    #[test]
+    fn code_keeps_its_line() {
+        let markdown = "# Title\n\nText\n\n## Section\n\n```rust,ignore {2}\nfn main() {}\n```\n";
+
+        assert_eq!(
+            render(markdown, None).unwrap(),
+            "# Title\n\n\n\n## Section\n\n```rust,ignore\nfn main() {}\n```\n"
+        );
+    }
+
+    #[test]
+    fn hidden_lines_are_compiled() {
+        let markdown = "```rust\n# fn main() {\n##[derive(Debug)]\nstruct A;\n# }\n```\n";
+
+        assert_eq!(
+            render(markdown, None).unwrap(),
+            "```rust\nfn main() {\n##[derive(Debug)]\nstruct A;\n}\n```\n"
+        );
+    }
+
+    #[test]
+    fn configured_hidden_lines_are_compiled() {
+        let markdown = "```rust,hidelines=!\n!use std::fmt;\n#![allow(unused)]\n```\n";
+
+        assert_eq!(
+            render(markdown, Some("~")).unwrap(),
+            "```rust\nuse std::fmt;\n##![allow(unused)]\n```\n"
+        );
+        assert_eq!(
+            render("```rust\n~use std::fmt;\n```\n", Some("~")).unwrap(),
+            "```rust\nuse std::fmt;\n```\n"
+        );
+    }
+
+    #[test]
+    fn fence_is_longer_than_the_code() {
+        let markdown = "````rust\nlet s = \"```\";\n````\n";
+
+        assert_eq!(
+            render(markdown, None).unwrap(),
+            "````rust\nlet s = \"```\";\n````\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn book_is_not_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("book.toml"), "[book]\ntitle = \"Book\"\n").unwrap();
+        std::fs::write(
+            root.join("src/SUMMARY.md"),
+            "- [Intro](intro.md)\n- [Missing](missing/chapter.md)\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/intro.md"), "# Intro\n").unwrap();
+
+        let before = tree(&root);
+
+        let command = CommandTest::parse_from(["test", root.to_str().unwrap()]);
+        let result = execute(&command).await;
+
+        assert!(result.is_err(), "The missing chapter cannot be tested");
+        assert_eq!(tree(&root), before);
+    }
+}
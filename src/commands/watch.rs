@@ -0,0 +1,514 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Error, Result};
+use clap::{Parser, ValueHint};
+use notify::{EventKind, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+
+use super::build::{open_book, Book, Rebuild};
+
+/// Time without changes to wait before rebuilding, as saving a file can
+/// trigger several events
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Debug, Parser)]
+pub struct CommandWatch {
+    /// Opens the compiled book in a web browser
+    #[clap(long, short)]
+    open: bool,
+    /// Output directory for the book\n\
+    /// Relative paths are interpreted relative to the book's root directory.\n\
+    /// If omitted, mdBook uses build.build-dir from book.toml \
+    /// or defaults to `./book`.
+    #[clap(long, short, value_hint = ValueHint::DirPath)]
+    dest_dir: Option<PathBuf>,
+    /// Root directory for the book
+    #[clap(value_hint = ValueHint::DirPath)]
+    dir: Option<PathBuf>,
+}
+
+pub async fn execute(
+    CommandWatch {
+        open,
+        dest_dir,
+        dir,
+    }: &CommandWatch,
+) -> Result<()> {
+    let dir = dir
+        .as_ref()
+        .unwrap_or(&PathBuf::from_str(".").unwrap())
+        .canonicalize()?;
+
+    let started = Instant::now();
+    let book = Book::load(&dir, dest_dir.as_deref()).await?;
+    book.build().await?;
+
+    println!(
+        "Book built at {} in {:.0?}",
+        book.build_dir.display(),
+        started.elapsed()
+    );
+
+    if *open {
+        open_book(&book.build_dir);
+    }
+
+    watch(book, dest_dir.as_deref(), |_| {}).await
+}
+
+/// Builds the book again every time its files change, until the watcher
+/// stops.
+///
+/// `on_build` is called after every build, successful or not
+pub(super) async fn watch(
+    mut book: Book,
+    dest_dir: Option<&Path>,
+    mut on_build: impl FnMut(Result<(), &Error>),
+) -> Result<()> {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+
+    let mut watcher = notify::recommended_watcher(move |event| match event {
+        Ok(event) => _ = sender.send(event),
+        Err(err) => log::error!("Cannot watch the book.\n  Cause: {err}"),
+    })
+    .map_err(|err| anyhow!("Cannot watch the book.\n  Cause: {err}"))?;
+
+    let mut watched = Vec::new();
+    update_watched_paths(&mut watcher, &mut watched, book.watched_paths())?;
+
+    println!("Watching for changes...");
+
+    while let Some(paths) = next_changes(&mut receiver, &book).await {
+        let started = Instant::now();
+
+        let result = if paths.iter().any(|path| book.needs_reload(path)) {
+            reload(&book, dest_dir).await.map(|new_book| {
+                book = new_book;
+                "Book rebuilt".to_string()
+            })
+        } else {
+            book.rebuild(&paths).await.map(describe_rebuild)
+        };
+
+        match &result {
+            Ok(message) if message.is_empty() => continue,
+            Ok(message) => println!("{message} in {:.0?}", started.elapsed()),
+            Err(err) => eprintln!("Build failed.\n  Cause: {err:#}"),
+        }
+
+        if let Err(err) = update_watched_paths(&mut watcher, &mut watched, book.watched_paths()) {
+            log::error!("{err}");
+        }
+
+        on_build(result.as_ref().map(|_| ()));
+    }
+
+    Ok(())
+}
+
+/// Loads the book at `dir` again every time its files change, until it can be
+/// loaded, for books that are broken when the server starts.
+///
+/// `on_build` is called after every attempt, and the loaded book is built
+pub(super) async fn wait_for_book(
+    dir: &Path,
+    dest_dir: Option<&Path>,
+    build_dir: &Path,
+    on_build: &mut impl FnMut(Result<(), &Error>),
+) -> Result<Book> {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+
+    let mut watcher = notify::recommended_watcher(move |event| match event {
+        Ok(event) => _ = sender.send(event),
+        Err(err) => log::error!("Cannot watch the book.\n  Cause: {err}"),
+    })
+    .map_err(|err| anyhow!("Cannot watch the book.\n  Cause: {err}"))?;
+
+    watcher
+        .watch(dir, RecursiveMode::Recursive)
+        .map_err(|err| anyhow!("Cannot watch {dir:?}.\n  Cause: {err}"))?;
+
+    println!("Watching for changes...");
+
+    load_when_fixed(&mut receiver, dir, dest_dir, build_dir, on_build).await
+}
+
+/// Loads the book at `dir` after the changes received by `receiver`, until it
+/// can be loaded
+async fn load_when_fixed(
+    receiver: &mut mpsc::UnboundedReceiver<notify::Event>,
+    dir: &Path,
+    dest_dir: Option<&Path>,
+    build_dir: &Path,
+    on_build: &mut impl FnMut(Result<(), &Error>),
+) -> Result<Book> {
+    // The build directory is ignored, so a failed build does not load the
+    // book again
+    let is_change = |event: notify::Event| {
+        !matches!(event.kind, EventKind::Access(_))
+            && event
+                .paths
+                .iter()
+                .any(|path| !path.starts_with(build_dir) && !is_temporary(path))
+    };
+
+    loop {
+        while !is_change(
+            receiver
+                .recv()
+                .await
+                .ok_or_else(|| anyhow!("The watcher stopped"))?,
+        ) {}
+        while let Ok(Some(_)) = tokio::time::timeout(DEBOUNCE, receiver.recv()).await {}
+
+        let book = match Book::load(dir, dest_dir).await {
+            Ok(book) => book,
+            Err(err) => {
+                eprintln!("Cannot load the book.\n  Cause: {err:#}");
+                on_build(Err(&err));
+                continue;
+            }
+        };
+
+        match book.build().await {
+            Ok(()) => {
+                println!("Book built at {}", book.build_dir.display());
+                on_build(Ok(()));
+            }
+            Err(err) => {
+                eprintln!("Build failed.\n  Cause: {err:#}");
+                on_build(Err(&err));
+            }
+        }
+
+        return Ok(book);
+    }
+}
+
+async fn reload(book: &Book, dest_dir: Option<&Path>) -> Result<Book> {
+    let book = Book::load(&book.dir, dest_dir).await?;
+    book.build().await?;
+
+    Ok(book)
+}
+
+/// Waits for a change, returning the paths changed until there are no more
+/// changes for a while
+async fn next_changes(
+    receiver: &mut mpsc::UnboundedReceiver<notify::Event>,
+    book: &Book,
+) -> Option<Vec<PathBuf>> {
+    let mut paths = HashSet::new();
+
+    while paths.is_empty() {
+        let event = receiver.recv().await?;
+        paths.extend(changed_paths(event, book));
+    }
+
+    while let Ok(Some(event)) = tokio::time::timeout(DEBOUNCE, receiver.recv()).await {
+        paths.extend(changed_paths(event, book));
+    }
+
+    Some(paths.into_iter().collect())
+}
+
+fn changed_paths(event: notify::Event, book: &Book) -> impl Iterator<Item = PathBuf> + '_ {
+    let is_change = !matches!(event.kind, EventKind::Access(_));
+
+    event
+        .paths
+        .into_iter()
+        .filter(move |path| is_change && !path.starts_with(&book.build_dir) && !is_temporary(path))
+}
+
+/// Editors write swap and backup files next to the ones being edited
+fn is_temporary(path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|name| name.to_string_lossy()) else {
+        return false;
+    };
+
+    name.starts_with('.') || name.ends_with('~') || name.ends_with(".swp")
+}
+
+fn update_watched_paths(
+    watcher: &mut impl Watcher,
+    watched: &mut Vec<(PathBuf, bool)>,
+    paths: Vec<(PathBuf, bool)>,
+) -> Result<()> {
+    for (path, _) in watched.iter().filter(|watched| !paths.contains(watched)) {
+        // The path can be already removed
+        _ = watcher.unwatch(path);
+    }
+
+    for (path, recursive) in paths.iter().filter(|path| !watched.contains(path)) {
+        let mode = if *recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+
+        watcher
+            .watch(path, mode)
+            .map_err(|err| anyhow!("Cannot watch {path:?}.\n  Cause: {err}"))?;
+    }
+
+    *watched = paths;
+
+    Ok(())
+}
+
+/// Summary of a rebuild, empty if nothing was written
+fn describe_rebuild(rebuild: Rebuild) -> String {
+    let plural = |count: usize, name: &str| match count {
+        1 => format!("1 {name}"),
+        count => format!("{count} {name}s"),
+    };
+
+    match rebuild {
+        Rebuild {
+            chapters: 0,
+            files: 0,
+        } => String::new(),
+        Rebuild { chapters, files: 0 } => format!("Rebuilt {}", plural(chapters, "chapter")),
+        Rebuild { chapters: 0, files } => format!("Copied {}", plural(files, "file")),
+        Rebuild { chapters, files } => format!(
+            "Rebuilt {} and copied {}",
+            plural(chapters, "chapter"),
+            plural(files, "file")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use notify::event::{AccessKind, ModifyKind};
+    use notify::Event;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Opens a small book in a temporary directory
+    async fn book() -> (TempDir, Book) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("book.toml"), "[book]\ntitle = \"Book\"\n").unwrap();
+        fs::write(root.join("src/SUMMARY.md"), "- [Intro](intro.md)\n").unwrap();
+        fs::write(root.join("src/intro.md"), "# Intro\n").unwrap();
+
+        let book = Book::open(&root, None).await.unwrap();
+
+        (dir, book)
+    }
+
+    fn modified(path: &Path) -> Event {
+        Event::new(EventKind::Modify(ModifyKind::Any)).add_path(path.to_path_buf())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn close_events_are_grouped() {
+        let (_dir, book) = book().await;
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let chapter = |name: &str| book.src_dir.join(name);
+
+        sender.send(modified(&chapter("a.md"))).unwrap();
+        sender.send(modified(&chapter("a.md"))).unwrap();
+
+        let later = sender.clone();
+        let (b, c) = (chapter("b.md"), chapter("c.md"));
+        tokio::spawn(async move {
+            tokio::time::sleep(DEBOUNCE / 4).await;
+            later.send(modified(&b)).unwrap();
+            tokio::time::sleep(DEBOUNCE * 3).await;
+            later.send(modified(&c)).unwrap();
+        });
+
+        let mut first = next_changes(&mut receiver, &book).await.unwrap();
+        first.sort();
+        assert_eq!(first, [chapter("a.md"), chapter("b.md")]);
+
+        let second = next_changes(&mut receiver, &book).await.unwrap();
+        assert_eq!(second, [chapter("c.md")]);
+
+        drop(sender);
+        assert_eq!(next_changes(&mut receiver, &book).await, None);
+    }
+
+    #[tokio::test]
+    async fn temporary_and_built_files_are_ignored() {
+        let (_dir, book) = book().await;
+        let changed = |event| changed_paths(event, &book).collect::<Vec<_>>();
+
+        for name in [".intro.md.swp", "intro.md~", "intro.swp", ".#intro.md"] {
+            assert!(
+                changed(modified(&book.src_dir.join(name))).is_empty(),
+                "{name}"
+            );
+        }
+
+        assert!(changed(modified(&book.build_dir.join("intro.html"))).is_empty());
+
+        let intro = book.src_dir.join("intro.md");
+        let read = Event::new(EventKind::Access(AccessKind::Any)).add_path(intro.clone());
+        assert!(changed(read).is_empty());
+        assert_eq!(changed(modified(&intro)), [intro]);
+    }
+
+    #[tokio::test]
+    async fn structural_changes_reload_the_book() {
+        let (dir, book) = book().await;
+        let root = dir.path().canonicalize().unwrap();
+
+        assert!(book.needs_reload(&root.join("book.toml")));
+        assert!(book.needs_reload(&root.join("src/SUMMARY.md")));
+        assert!(book.needs_reload(&root.join("theme/index.hbs")));
+        assert!(!book.needs_reload(&root.join("src/intro.md")));
+
+        // New files are only found when the book is loaded again
+        fs::write(root.join("src/image.png"), "").unwrap();
+        assert!(book.needs_reload(&root.join("src/image.png")));
+    }
+
+    #[test]
+    fn rebuilds_are_described() {
+        let describe = |chapters, files| describe_rebuild(Rebuild { chapters, files });
+
+        assert_eq!(describe(0, 0), "");
+        assert_eq!(describe(1, 0), "Rebuilt 1 chapter");
+        assert_eq!(describe(0, 2), "Copied 2 files");
+        assert_eq!(describe(3, 1), "Rebuilt 3 chapters and copied 1 file");
+    }
+
+    /// Records the watched paths instead of watching them
+    #[derive(Default)]
+    struct FakeWatcher {
+        watched: Vec<(PathBuf, bool)>,
+    }
+
+    impl Watcher for FakeWatcher {
+        fn new<F: notify::EventHandler>(_: F, _: notify::Config) -> notify::Result<Self> {
+            Ok(Self::default())
+        }
+
+        fn watch(&mut self, path: &Path, recursive_mode: RecursiveMode) -> notify::Result<()> {
+            if !path.exists() {
+                return Err(notify::Error::path_not_found());
+            }
+
+            let recursive = recursive_mode == RecursiveMode::Recursive;
+            self.watched.push((path.to_path_buf(), recursive));
+
+            Ok(())
+        }
+
+        fn unwatch(&mut self, path: &Path) -> notify::Result<()> {
+            self.watched.retain(|(watched, _)| watched != path);
+
+            Ok(())
+        }
+
+        fn kind() -> notify::WatcherKind {
+            notify::WatcherKind::NullWatcher
+        }
+    }
+
+    #[tokio::test]
+    async fn watched_paths_follow_the_book() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let (src, extra, summary) = (
+            root.join("src"),
+            root.join("extra"),
+            root.join("src/SUMMARY.md"),
+        );
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&extra).unwrap();
+        fs::write(&summary, "").unwrap();
+
+        let mut watcher = FakeWatcher::default();
+        let mut watched = Vec::new();
+
+        let paths = vec![(src.clone(), true), (extra.clone(), true)];
+        update_watched_paths(&mut watcher, &mut watched, paths.clone()).unwrap();
+
+        assert_eq!(watched, paths);
+        assert_eq!(watcher.watched, paths);
+
+        let paths = vec![(src.clone(), true), (summary.clone(), false)];
+        update_watched_paths(&mut watcher, &mut watched, paths.clone()).unwrap();
+
+        assert_eq!(watched, paths);
+        assert_eq!(watcher.watched, paths);
+
+        let missing = vec![(root.join("missing"), true)];
+        assert!(update_watched_paths(&mut watcher, &mut watched, missing).is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn broken_book_is_loaded_once_fixed() {
+        let (dir, _) = book().await;
+        let root = dir.path().canonicalize().unwrap();
+        let book_toml = root.join("book.toml");
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        fs::write(&book_toml, "[book\n").unwrap();
+        sender.send(modified(&book_toml)).unwrap();
+
+        // The book is fixed after the first attempt fails
+        let mut results = Vec::new();
+        let mut on_build = |result: Result<(), &Error>| {
+            if result.is_err() {
+                fs::write(&book_toml, "[book]\ntitle = \"Book\"\n").unwrap();
+                sender.send(modified(&book_toml)).unwrap();
+            }
+            results.push(result.is_ok());
+        };
+
+        let book = load_when_fixed(
+            &mut receiver,
+            &root,
+            None,
+            &root.join("book"),
+            &mut on_build,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(book.build_dir, root.join("book"));
+        assert_eq!(results, [false, true]);
+        assert!(root.join("book/index.html").is_file());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn changes_of_the_build_directory_do_not_load_the_book() {
+        let (dir, _) = book().await;
+        let root = dir.path().canonicalize().unwrap();
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+
+        sender
+            .send(modified(&root.join("book/index.html")))
+            .unwrap();
+        sender
+            .send(modified(&root.join("src/.intro.md.swp")))
+            .unwrap();
+        drop(sender);
+
+        let mut on_build = |_: Result<(), &Error>| panic!("The book was loaded");
+        let result = load_when_fixed(
+            &mut receiver,
+            &root,
+            None,
+            &root.join("book"),
+            &mut on_build,
+        )
+        .await;
+
+        assert!(result.is_err());
+    }
+}
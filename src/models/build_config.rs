@@ -18,6 +18,8 @@ pub struct BuildConfig {
     pub use_default_preprocessors: bool,
     /// Extra directories to trigger rebuild when watching/serving
     pub extra_watch_dirs: Vec<PathBuf>,
+    /// Should the warnings of the summaries fail the build?
+    pub strict: bool,
 }
 
 impl Default for BuildConfig {
@@ -27,6 +29,7 @@ impl Default for BuildConfig {
             create_missing: true,
             use_default_preprocessors: true,
             extra_watch_dirs: Vec::new(),
+            strict: false,
         }
     }
 }
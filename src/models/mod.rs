@@ -20,6 +20,7 @@ pub mod rust_config;
 /// The overall configuration object for MDBook, essentially an in-memory
 /// representation of `book.toml`.
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     /// Metadata about the book.
     pub book: BookConfig,
@@ -55,6 +56,36 @@ impl Config {
         self.output.clone().unwrap_or_default().html
     }
 
+    /// Output directory of the book at `root`, which is `out_dir` or the
+    /// `build.build-dir` of the configuration, relative to the root
+    pub fn build_dir(&self, root: &Path, out_dir: Option<&Path>) -> PathBuf {
+        match (out_dir, &self.build) {
+            (Some(out_dir), _) => root.join(out_dir),
+            (None, Some(build)) => root.join(&build.build_dir),
+            (None, None) => root.join(BuildConfig::default().build_dir),
+        }
+    }
+
+    /// Path where the book is published, from `output.html.site-url`, with
+    /// leading and trailing slashes.
+    ///
+    /// The scheme and host of a full URL are ignored, so
+    /// `https://example.com/libro` is published at `/libro/`
+    pub fn site_path(&self) -> String {
+        let site_url = self.html_config().site_url.unwrap_or_default();
+
+        let path = match site_url.split_once("://") {
+            Some((_, url)) => url.find('/').map_or("", |index| &url[index..]),
+            None => &site_url,
+        };
+        let path = path.trim_matches('/');
+
+        match path.is_empty() {
+            true => "/".to_string(),
+            false => format!("/{path}/"),
+        }
+    }
+
     /// Gets the language configured for a book.
     pub fn get_language<I: AsRef<str>>(&self, index: Option<I>) -> Result<Option<String>> {
         match self.default_language() {
@@ -10,22 +10,16 @@ pub struct PreprocessorsConfig {
     pub others: HashMap<String, Preprocessor>,
 }
 
-///
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct Preprocessor {
-    ///
     pub after: Option<Vec<String>>,
-    ///
     pub before: Option<Vec<String>>,
-    ///
     pub command: Option<String>,
-    ///
     pub renders: Option<Vec<String>>,
     pub optional: Option<bool>,
 }
 
-///
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct HtmlPreprocessor {
@@ -65,31 +59,27 @@ pub struct HtmlPrint {
 #[serde(default, rename_all = "kebab-case")]
 pub struct HtmlFold {
     /// whether or not to enable section folding
-    enable: bool,
+    pub enable: bool,
     /// the depth to start folding
-    level: Option<u16>,
+    pub level: Option<u16>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct HtmlPlayground {
-    ///
     editable: Option<bool>,
-    ///
     copyable: Option<bool>,
-    ///
     copy_js: Option<bool>,
-    ///
     runnable: Option<bool>,
-    ///
-    line_numbers: Option<bool>,
+    /// displays line numbers for editable code
+    pub line_numbers: Option<bool>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct HtmlCode {
-    ///
-    hidelines: Option<HashMap<String, String>>,
+    /// prefixes of the hidden lines for each language
+    pub hidelines: Option<HashMap<String, String>>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -110,6 +100,5 @@ pub struct HtmlSearch {
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct HtmlRedirect {
-    ///
     redirect: HashMap<String, String>,
 }
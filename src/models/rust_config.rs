@@ -4,13 +4,16 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct RustConfig {
-    /// Rust edition used in playground
+    /// Rust edition used in playground and to test the code
     pub edition: Option<RustEdition>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 /// Rust edition to use for the code.
 pub enum RustEdition {
+    /// The 2024 edition of Rust
+    #[serde(rename = "2024")]
+    E2024,
     /// The 2021 edition of Rust
     #[serde(rename = "2021")]
     E2021,
@@ -21,3 +24,15 @@ pub enum RustEdition {
     #[serde(rename = "2015")]
     E2015,
 }
+
+impl RustEdition {
+    /// Year of the edition, as rustc and rustdoc expect it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RustEdition::E2024 => "2024",
+            RustEdition::E2021 => "2021",
+            RustEdition::E2018 => "2018",
+            RustEdition::E2015 => "2015",
+        }
+    }
+}
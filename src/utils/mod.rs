@@ -1,2 +1,4 @@
+mod normalize_path;
 mod safe_remove;
+pub use normalize_path::NormalizePath;
 pub use safe_remove::SafeRemove;
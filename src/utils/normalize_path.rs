@@ -0,0 +1,28 @@
+use std::path::{Component, Path, PathBuf};
+
+pub trait NormalizePath {
+    /// Lexically resolves `.` and `..` components without touching the filesystem
+    fn normalize(&self) -> PathBuf;
+}
+
+impl NormalizePath for Path {
+    fn normalize(&self) -> PathBuf {
+        let mut normalized = PathBuf::new();
+
+        for component in self.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => match normalized.components().next_back() {
+                    Some(Component::Normal(_)) => {
+                        normalized.pop();
+                    }
+                    Some(Component::RootDir | Component::Prefix(_)) => {}
+                    _ => normalized.push(component),
+                },
+                component => normalized.push(component),
+            }
+        }
+
+        normalized
+    }
+}